@@ -0,0 +1,151 @@
+//! A headless conformance runner for Ferrous, for CI.
+//!
+//! Each ROM is run deterministically, with a seeded random number
+//! generator and no input, and the digest of its final screen is
+//! compared against the expected one, printing PASS or FAIL.
+//!
+//! Running with `--record ROM...` instead prints the name and digest
+//! of each given ROM, run for 300 frames, for adding it to `CASES`.
+
+use std::path::Path;
+
+/// Seed of the random number generator, so that Cxkk is reproducible.
+const SEED: u64 = 0xC8;
+
+/// CPU cycles to execute per frame.
+const CYCLES_PER_FRAME: u16 = 10;
+
+/// Frames a recorded ROM is run for, unless it halts earlier.
+const RECORD_FRAMES: u32 = 300;
+
+/// A ROM and the digest of its screen after the run.
+struct Case {
+    /// Name printed with the result.
+    name: &'static str,
+
+    /// Bytes of the ROM.
+    rom: &'static [u8],
+
+    /// Quirks the ROM expects.
+    profile: ferrous::QuirkProfile,
+
+    /// Frames the ROM is run for, unless it halts earlier.
+    frames: u32,
+
+    /// Expected `vram_digest` of the final screen.
+    digest: u64,
+}
+
+/// ROMs checked by default.
+///
+/// Only ROMs that are part of the repository are embedded. Well known test ROMs,
+/// e.g. BC_test or test_opcode, can be added by recording their digests.
+const CASES: [Case; 3] = [
+    Case {
+        name: "splash",
+        rom: include_bytes!("../../ferrous-native/src/assets/roms/splash.ch8"),
+        profile: ferrous::QuirkProfile::Cosmac,
+        frames: 60,
+        digest: 0x7C43_9B63_8086_210A,
+    },
+    Case {
+        name: "keypad",
+        rom: include_bytes!("../../ferrous-native/src/assets/roms/keypad.ch8"),
+        profile: ferrous::QuirkProfile::Cosmac,
+        frames: 60,
+        digest: 0x1BAD_7C6B_4704_F5DD,
+    },
+    // Stops while the ball is drawn, rather than between erasing and redrawing it.
+    Case {
+        name: "bounce",
+        rom: include_bytes!("../../ferrous-native/src/assets/roms/bounce.ch8"),
+        profile: ferrous::QuirkProfile::Cosmac,
+        frames: 250,
+        digest: 0xCDF1_48F5_E0E3_4F9B,
+    },
+];
+
+/// Run the ROM deterministically, and return the digest of its final screen.
+fn run(rom: &[u8], profile: ferrous::QuirkProfile, frames: u32) -> Result<u64, &'static str> {
+    let mut cpu = ferrous::Ferrous::new();
+    cpu.apply_profile(profile);
+    cpu.set_rng_seed(Some(SEED));
+    cpu.load_rom(rom)?;
+
+    for _ in 0..frames {
+        if cpu.is_halted {
+            break;
+        }
+
+        cpu.advance_frame(CYCLES_PER_FRAME);
+    }
+
+    Ok(cpu.vram_digest())
+}
+
+/// Print the digests of the given ROM files, as entries for `CASES`.
+fn record(paths: &[String]) -> bool {
+    let mut ok = true;
+
+    for path in paths {
+        let name = Path::new(path)
+            .file_stem()
+            .map_or(path.clone(), |stem| stem.to_string_lossy().into_owned());
+
+        let digest = std::fs::read(path)
+            .map_err(|_| "failed to read the ROM.")
+            .and_then(|rom| run(&rom, ferrous::QuirkProfile::Cosmac, RECORD_FRAMES));
+
+        match digest {
+            Ok(digest) => println!("{:<16} {:#018X}", name, digest),
+            Err(error) => {
+                eprintln!("[ERROR] {}: {}", path, error);
+                ok = false;
+            }
+        }
+    }
+
+    ok
+}
+
+/// Run every case, printing PASS or FAIL for each.
+fn check() -> bool {
+    let mut passed = 0;
+
+    for case in CASES.iter() {
+        match run(case.rom, case.profile, case.frames) {
+            Ok(digest) if digest == case.digest => {
+                println!("PASS {}", case.name);
+                passed += 1;
+            }
+
+            Ok(digest) => println!(
+                "FAIL {}: expected {:#018X}, found {:#018X}",
+                case.name, case.digest, digest
+            ),
+
+            Err(error) => println!("FAIL {}: {}", case.name, error),
+        }
+    }
+
+    println!("{}/{} passed", passed, CASES.len());
+
+    passed == CASES.len()
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let ok = match args.split_first() {
+        Some((flag, paths)) if flag == "--record" => record(paths),
+        Some(_) => {
+            eprintln!("Usage: ferrous-conformance [--record ROM...]");
+            std::process::exit(2);
+        }
+        None => check(),
+    };
+
+    if !ok {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,135 @@
+//! A lightweight frontend for Ferrous, built on minifb.
+//! It has no debugger or menus, and just plays ROMs.
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use minifb::{Key, Scale, Window, WindowOptions};
+
+/// Width of the window's framebuffer, the high resolution width.
+const WIDTH: usize = ferrous::Resolution::HIGH.width;
+
+/// Height of the window's framebuffer, the high resolution height.
+const HEIGHT: usize = ferrous::Resolution::HIGH.height;
+
+/// Instructions to execute per second, 10 per 60 Hz tick.
+const INSTRUCTIONS_PER_SECOND: u32 = 600;
+
+/// Longest time caught up on at once, e.g. after the window was dragged.
+const MAX_CATCHUP: Duration = Duration::from_millis(100);
+
+/// Foreground color, in 0RGB format.
+const FOREGROUND: u32 = 0x00FF_FFFF;
+
+/// Background color, in 0RGB format.
+const BACKGROUND: u32 = 0x0000_0000;
+
+/// Keyboard keys, in the order of the Chip-8 keys they map to.
+const KEYMAP: [Key; 16] = [
+    Key::X,
+    Key::Key1,
+    Key::Key2,
+    Key::Key3,
+    Key::Q,
+    Key::W,
+    Key::E,
+    Key::A,
+    Key::S,
+    Key::D,
+    Key::Z,
+    Key::C,
+    Key::Key4,
+    Key::R,
+    Key::F,
+    Key::V,
+];
+
+/// Convert the interpreter's screen to a 0RGB buffer, doubling
+/// the pixels in low resolution mode to fill the window.
+fn update_buffer(cpu: &ferrous::Ferrous, buffer: &mut [u32]) {
+    let (rows, cols) = cpu.get_height_width();
+    let vram = cpu.get_video_buffer();
+    let scale = WIDTH / cols;
+
+    for (i, pixel) in buffer.iter_mut().enumerate() {
+        let x = (i % WIDTH) / scale;
+        let y = (i / WIDTH) / scale;
+
+        *pixel = if y < rows && vram[x + y * cols] != 0 {
+            FOREGROUND
+        } else {
+            BACKGROUND
+        };
+    }
+}
+
+fn main() {
+    let path = match std::env::args_os().nth(1) {
+        Some(path) => PathBuf::from(path),
+        None => {
+            eprintln!("Usage: ferrous-minifb <ROM>");
+            std::process::exit(1);
+        }
+    };
+
+    let data = match std::fs::read(&path) {
+        Ok(data) => data,
+        Err(error) => {
+            eprintln!("[ERROR] failed to read {}, {}", path.display(), error);
+            std::process::exit(1);
+        }
+    };
+
+    let mut cpu = ferrous::Ferrous::new();
+
+    if let Err(error) = cpu.load_rom(&data) {
+        eprintln!("[ERROR] failed to load {}, {}", path.display(), error);
+        std::process::exit(1);
+    }
+
+    let mut window = Window::new(
+        "Ferrous Chip-8",
+        WIDTH,
+        HEIGHT,
+        WindowOptions {
+            scale: Scale::X8,
+            ..WindowOptions::default()
+        },
+    )
+    .expect("Failed to create the window.");
+
+    // Roughly 60 frames per second.
+    window.limit_update_rate(Some(Duration::from_micros(16_667)));
+
+    let mut buffer = vec![BACKGROUND; WIDTH * HEIGHT];
+    let mut clock = ferrous::TickClock::new();
+    let mut last_frame = Instant::now();
+
+    while window.is_open() && !window.is_key_down(Key::Escape) {
+        let held = KEYMAP
+            .iter()
+            .enumerate()
+            .filter(|(_, key)| window.is_key_down(**key))
+            .fold(0, |keys, (index, _)| keys | 1 << index);
+
+        cpu.apply_input_diff(held, !held);
+
+        // The update rate is only a limit, so the timers are stepped
+        // by the time that actually passed, rather than once per frame.
+        let now = Instant::now();
+        clock.advance((now - last_frame).min(MAX_CATCHUP));
+        last_frame = now;
+
+        while let Some(cycles) = clock.next_tick(INSTRUCTIONS_PER_SECOND) {
+            if cpu.advance_frame(cycles) > 0 {
+                eprintln!("[WARN] invalid or unknown opcode encountered.");
+            }
+        }
+
+        update_buffer(&cpu, &mut buffer);
+
+        window
+            .update_with_buffer(&buffer, WIDTH, HEIGHT)
+            .expect("Failed to update the window.");
+    }
+}
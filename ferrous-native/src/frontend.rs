@@ -1,34 +1,155 @@
 //! Contains helper functions and the main entry point for the frontend.
 
+use std::path::PathBuf;
+use std::time::Duration;
+
 use glium::glutin::ContextBuilder;
 use glium::glutin::{
-    dpi::LogicalSize,
+    dpi::{LogicalSize, PhysicalPosition, PhysicalSize, Size},
     event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
-    window::WindowBuilder,
+    window::{Fullscreen, WindowBuilder},
 };
 use glium::{Display, Surface};
 
+#[cfg(feature = "audio")]
 mod audio;
+mod crash;
+mod crt;
+mod flags;
 mod fps_limiter;
 mod gui;
+#[cfg(feature = "hot-reload")]
+mod hot_reload;
+mod key_map;
+mod palette;
+mod profile;
+mod sidecar;
+#[cfg(not(feature = "audio"))]
+mod silent_audio;
+mod watch;
+mod window_config;
+
+#[cfg(not(feature = "audio"))]
+use silent_audio as audio;
 
+use crate::input_script::InputScript;
 use fps_limiter::FpsLimiter;
+use key_map::KeyMap;
+pub use sidecar::parse_hex_color;
+use window_config::WindowConfig;
+
+/// Options passed to the frontend from the command line.
+pub struct Options {
+    /// ROM to load and start running immediately.
+    pub rom: Option<PathBuf>,
+
+    /// CPU cycles to execute per frame.
+    pub cycles: Option<u16>,
+
+    /// Frames to catch up on after a stall, see `FpsLimiter::set_max_catchup_frames`.
+    pub max_catchup_frames: Option<u32>,
+
+    /// Should the window start in fullscreen mode?
+    pub fullscreen: bool,
+
+    /// Integer scale of the initial window size.
+    /// If not given, the window size of the last session is restored.
+    pub scale: Option<u32>,
+
+    /// Quirk profile to apply before running.
+    pub quirks: Option<ferrous::QuirkProfile>,
+
+    /// Enable the load/store quirk regardless of the profile.
+    pub load_store_quirk: bool,
+
+    /// Enable the shift quirk regardless of the profile.
+    pub shift_quirk: bool,
+
+    /// Enable the jump quirk regardless of the profile.
+    pub jump_quirk: bool,
+
+    /// Enable the logic quirk regardless of the profile.
+    pub logic_quirk: bool,
+
+    /// Foreground color, as `#RRGGBB`.
+    pub fg_color: Option<String>,
+
+    /// Background color, as `#RRGGBB`.
+    pub bg_color: Option<String>,
+
+    /// Run without a window, for the given number of frames.
+    pub headless: bool,
+
+    /// Number of frames to run in headless mode.
+    pub frames: u32,
+
+    /// Print the framebuffer after a headless run.
+    pub dump_vram: bool,
+
+    /// Print the sound events after a headless run.
+    pub dump_sound: bool,
+
+    /// Reference trace to check the ROM's execution against.
+    pub reference_trace: Option<PathBuf>,
+
+    /// Key presses to apply on given frames.
+    pub input_script: Option<InputScript>,
+}
 
 /// Initialize the window, and then glium's
 /// display.
-fn initialize_display(event_loop: &EventLoop<()>) -> Display {
+fn initialize_display(
+    event_loop: &EventLoop<()>,
+    options: &Options,
+    saved: Option<WindowConfig>,
+) -> Display {
+    let vsync = saved.map_or(false, |config| config.vsync);
+
+    // An explicit scale takes precedence over the last session's geometry.
+    let geometry = match options.scale {
+        Some(_) => None,
+        None => saved.map(|config| config.clamp_to_monitors(event_loop)),
+    };
+
+    let size: Size = match geometry {
+        Some(config) => PhysicalSize::new(config.size[0], config.size[1]).into(),
+        None => {
+            let scale = options.scale.unwrap_or(9);
+
+            LogicalSize::new(128 * scale, 64 * scale).into()
+        }
+    };
+
+    let fullscreen = if options.fullscreen {
+        Some(Fullscreen::Borderless(None))
+    } else {
+        None
+    };
+
     // Create a GL context, and a window.
-    let cb = ContextBuilder::new();
+    // The swap interval is fixed once the context is created,
+    // so changing the V-Sync option takes effect after a restart.
+    let cb = ContextBuilder::new().with_vsync(vsync);
     let wb = WindowBuilder::new()
         .with_decorations(true)
         .with_title("Ferrous Chip-8")
+        .with_fullscreen(fullscreen)
         .with_min_inner_size(LogicalSize::new(128, 64))
-        .with_inner_size(LogicalSize::new(1152, 576));
+        .with_inner_size(size);
 
     // Create the glium display, and clear it.
     let display = Display::new(wb, cb, &event_loop).expect("Failed to initialize the display.");
 
+    if let Some(config) = geometry {
+        let [x, y] = config.position;
+
+        display
+            .gl_window()
+            .window()
+            .set_outer_position(PhysicalPosition::new(x, y));
+    }
+
     let mut frame = display.draw();
     frame.clear_color_srgb(0.0, 0.0, 0.0, 1.0);
     frame.finish().expect("Failed to swap buffers.");
@@ -37,49 +158,215 @@ fn initialize_display(event_loop: &EventLoop<()>) -> Display {
 }
 
 /// Handle events provided by the OS.
-fn handle_keyboard_event(cpu: &mut ferrous::Ferrous, input: &KeyboardInput) {
+fn handle_keyboard_event(cpu: &mut ferrous::Ferrous, key_map: KeyMap, input: &KeyboardInput) {
     if let KeyboardInput {
         virtual_keycode: Some(keycode),
         state,
         ..
     } = input
     {
-        let index = match keycode {
-            VirtualKeyCode::Key1 => Some(0x1),
-            VirtualKeyCode::Key2 => Some(0x2),
-            VirtualKeyCode::Key3 => Some(0x3),
-            VirtualKeyCode::Key4 => Some(0xC),
-            VirtualKeyCode::Q => Some(0x4),
-            VirtualKeyCode::W => Some(0x5),
-            VirtualKeyCode::E => Some(0x6),
-            VirtualKeyCode::R => Some(0xD),
-            VirtualKeyCode::A => Some(0x7),
-            VirtualKeyCode::S => Some(0x8),
-            VirtualKeyCode::D => Some(0x9),
-            VirtualKeyCode::F => Some(0xE),
-            VirtualKeyCode::Z => Some(0xA),
-            VirtualKeyCode::X => Some(0x0),
-            VirtualKeyCode::C => Some(0xB),
-            VirtualKeyCode::V => Some(0xF),
-            _ => None,
-        };
+        let index = key_map.key_index(*keycode);
 
         if let Some(i) = index {
-            cpu.set_key_at_index(i, *state == ElementState::Pressed);
+            let pressed = *state == ElementState::Pressed;
+
+            if pressed {
+                cpu.queue_key_press(i as u8);
+            }
+
+            cpu.set_key_at_index(i, pressed);
         }
     }
 }
 
+/// Apply the quirk profile and explicit quirk flags to the CPU.
+pub fn apply_quirks(cpu: &mut ferrous::Ferrous, options: &Options) {
+    if let Some(profile) = options.quirks {
+        cpu.apply_profile(profile);
+    }
+
+    // Explicit quirk flags take precedence over the profile.
+    if options.load_store_quirk {
+        cpu.set_load_store(true);
+    }
+
+    if options.shift_quirk {
+        cpu.set_shift(true);
+    }
+
+    if options.jump_quirk {
+        cpu.set_jump(true);
+    }
+
+    if options.logic_quirk {
+        cpu.set_logic(true);
+    }
+}
+
+/// Log why the instruction at the given address couldn't be executed.
+/// The invalid opcode policy has already been applied by the interpreter.
+pub fn log_failed_cycle(cpu: &ferrous::Ferrous, pc: usize) {
+    if let Some(error) = cpu.stack_error {
+        warn!("stack {:?} at {:#05X}, interpreter halted.", error, pc);
+    } else if let Some(&[high, low]) = cpu.memory_slice(pc..pc + 2) {
+        warn!(
+            "invalid or unknown opcode {:#06X} at {:#05X}.",
+            u16::from_be_bytes([high, low]),
+            pc
+        );
+    }
+}
+
+/// Was the last executed opcode invalid, rather than `00FD`, when the
+/// interpreter halted? This only happens with the halt policy for invalid opcodes.
+fn halted_by_invalid_opcode(cpu: &ferrous::Ferrous) -> bool {
+    match cpu.memory_slice(cpu.pc.saturating_sub(2)..cpu.pc) {
+        Some(&[high, low]) => {
+            ferrous::Instruction::decode(u16::from_be_bytes([high, low])).is_none()
+        }
+        _ => false,
+    }
+}
+
+/// Run up to the given number of cycles, logging failed cycles, and
+/// return the address of the breakpoint execution stopped at, if any.
+fn run_cycles(cpu: &mut ferrous::Ferrous, cycles: u16) -> Option<usize> {
+    let mut remaining = cycles as usize;
+
+    loop {
+        let summary = cpu.step_n(remaining);
+        remaining -= summary.executed;
+
+        match summary.stopped {
+            Some(ferrous::StopReason::Breakpoint(address)) => return Some(address),
+
+            Some(ferrous::StopReason::InvalidOpcode(_)) => log_failed_cycle(cpu, cpu.pc - 2),
+
+            Some(ferrous::StopReason::Halted) => {
+                // Only log the cycle that halted the interpreter, not halting by EXIT.
+                if summary.executed > 0 {
+                    if cpu.stack_error.is_some() {
+                        log_failed_cycle(cpu, cpu.pc);
+                    } else if halted_by_invalid_opcode(cpu) {
+                        log_failed_cycle(cpu, cpu.pc - 2);
+                    }
+                }
+
+                return None;
+            }
+
+            None => {}
+        }
+
+        // `step_n` doesn't stop at a breakpoint on its first cycle,
+        // so check the address it stopped at, before the next call.
+        if summary.executed > 0 && cpu.is_breakpoint(cpu.pc) {
+            if cpu.temporary_breakpoint() == Some(cpu.pc) {
+                cpu.clear_temporary_breakpoint();
+            }
+
+            return Some(cpu.pc);
+        }
+
+        if remaining == 0 {
+            return None;
+        }
+    }
+}
+
+/// Handle emulator hotkeys, that aren't part of the keypad.
+fn handle_hotkey(state: &mut gui::State, input: &KeyboardInput) {
+    if let KeyboardInput {
+        virtual_keycode: Some(keycode),
+        state: ElementState::Pressed,
+        ..
+    } = input
+    {
+        let cycles = match keycode {
+            VirtualKeyCode::PageUp | VirtualKeyCode::Equals => {
+                state.cycles_per_frame.saturating_add(1)
+            }
+
+            VirtualKeyCode::PageDown | VirtualKeyCode::Minus => {
+                state.cycles_per_frame.saturating_sub(1)
+            }
+
+            _ => return,
+        };
+
+        state.cycles_per_frame = cycles.clamp(*gui::CYCLES_RANGE.start(), *gui::CYCLES_RANGE.end());
+        state.show_toast(format!("Cycles per Frame: {}", state.cycles_per_frame));
+    }
+}
+
 /// Start the emulator, and run until
 /// the user requests quitting.
-pub fn start() {
+pub fn start(options: Options) {
+    crash::install_hook();
+
     // Create the event loop and initialize the glium display.
     let event_loop = EventLoop::new();
     let audio = audio::Audio::new();
-    let display = initialize_display(&event_loop);
+    let saved_window = WindowConfig::load();
+    let display = initialize_display(&event_loop, &options, saved_window);
     let mut user_interface = gui::UserInterface::new(&display);
     let mut cpu = ferrous::Ferrous::new();
     let mut fps_limiter = FpsLimiter::new();
+    let mut clock = ferrous::TickClock::new();
+
+    if let Some(frames) = options.max_catchup_frames {
+        fps_limiter.set_max_catchup_frames(frames);
+    }
+
+    if let Some(path) = &options.rom {
+        if gui::load_rom_file(&mut user_interface.state, &mut cpu, path) {
+            user_interface.state.emulator_state = gui::EmulatorState::Running;
+        }
+    }
+
+    // Command line options take precedence over the ROM's sidecar file,
+    // but are not written back to it.
+    apply_quirks(&mut cpu, &options);
+
+    // Picking a platform also picks its speed, unless the cycles are given too.
+    if options.quirks.is_some() && options.cycles.is_none() {
+        user_interface.state.ips_mode = true;
+        user_interface.state.instructions_per_second = cpu.instructions_per_second;
+    }
+
+    if let Some(cycles) = options.cycles {
+        user_interface.state.cycles_per_frame = cycles;
+    }
+
+    if let Some(hex) = &options.fg_color {
+        if let Err(error) = user_interface.state.set_fg_hex(hex) {
+            warn!("ignoring foreground color, {}", error);
+        }
+    }
+
+    if let Some(hex) = &options.bg_color {
+        if let Err(error) = user_interface.state.set_bg_hex(hex) {
+            warn!("ignoring background color, {}", error);
+        }
+    }
+
+    user_interface.state.sync_rom_config(&cpu);
+
+    // Frames are only paced by the display if the context was created with V-Sync.
+    let context_vsync = saved_window.map_or(false, |config| config.vsync);
+    user_interface.state.vsync = context_vsync;
+
+    // Updated from the interpreter's sound events, and after it is reset.
+    let mut beeping = false;
+
+    // Frames emulated since the last rendered frame.
+    let mut skipped_frames = 0;
+
+    // Frames emulated while running, for the input script.
+    let mut frame = 0;
+
+    #[cfg(feature = "hot-reload")]
+    let mut rom_watcher = hot_reload::RomWatcher::new();
 
     event_loop.run(move |event, _, control_flow| {
         user_interface.handle_event(&display, &event);
@@ -88,9 +375,13 @@ pub fn start() {
             Event::NewEvents(_) => {
                 let delta = fps_limiter.update();
                 user_interface.update_delta(delta);
+                clock.advance(delta);
             }
 
             Event::MainEventsCleared => {
+                #[cfg(feature = "hot-reload")]
+                rom_watcher.update(&mut user_interface.state, &mut cpu);
+
                 user_interface.prepare_frame(&display);
             }
 
@@ -98,33 +389,116 @@ pub fn start() {
                 use gui::EmulatorState::*;
 
                 match user_interface.state.emulator_state {
+                    // Timers are stepped at 60 Hz, and the CPU runs its share of the
+                    // instructions per second in between, however fast frames are drawn.
                     Running => {
-                        for _ in 0..user_interface.state.cycles_per_frame {
-                            if cpu.execute_cycle().is_none() {
-                                eprintln!("[WARN] invalid or unknown opcode encountered.");
+                        let ips = user_interface.state.effective_ips();
+
+                        while let Some(cycles) = clock.next_tick(ips) {
+                            crash::record_state(&cpu);
+
+                            if let Some(script) = &options.input_script {
+                                script.apply(&mut cpu, frame);
                             }
-                        }
 
-                        cpu.step_timers();
+                            frame += 1;
+
+                            user_interface.state.frame_cycles = cycles;
+
+                            let stopped_at = run_cycles(&mut cpu, cycles);
+                            cpu.step_timers();
+
+                            if let Some(address) = stopped_at {
+                                user_interface.state.emulator_state = Paused;
+                                user_interface
+                                    .state
+                                    .show_toast(format!("Stopped at {:#05X}.", address));
+
+                                clock.reset();
+                            }
+                        }
                     }
 
                     Quit => *control_flow = ControlFlow::Exit,
 
-                    _ => {}
+                    _ => clock.reset(),
+                }
+
+                for cpu_event in cpu.drain_events() {
+                    match cpu_event {
+                        ferrous::CpuEvent::SoundOn => beeping = true,
+                        ferrous::CpuEvent::SoundOff => beeping = false,
+
+                        ferrous::CpuEvent::ModeChange { is_highres } => info!(
+                            "switched to {} resolution at {:#05X}.",
+                            if is_highres { "high" } else { "low" },
+                            cpu.pc
+                        ),
+
+                        ferrous::CpuEvent::Halt => info!("interpreter halted at {:#05X}.", cpu.pc),
+                    }
+                }
+
+                audio.set_envelope(
+                    Duration::from_millis(user_interface.state.beep_attack as u64),
+                    Duration::from_millis(user_interface.state.beep_release as u64),
+                );
+                audio.set_pan(user_interface.state.beep_pan);
+
+                // The sound timer is frozen while paused, so the beep is
+                // faded out as soon as emulation stops running, and back in after.
+                audio.set_paused(user_interface.state.emulator_state != Running);
+
+                // A loaded state doesn't emit sound events, so the beep is
+                // taken from the interpreter, rather than the last event.
+                if std::mem::take(&mut user_interface.state.was_reset) {
+                    beeping = cpu.should_beep();
+
+                    if !beeping {
+                        audio.cut_beep();
+                    }
                 }
 
-                if cpu.st > 0 && user_interface.state.emulator_state == Running {
+                if beeping {
                     audio.play_beep();
                 } else {
                     audio.pause_beep();
                 }
 
-                user_interface.update_framebuffer(&cpu);
-                user_interface.render_ui(&display, &mut cpu);
+                // Timers and audio are still updated on skipped frames,
+                // only the texture upload and rendering are skipped.
+                if user_interface.state.emulator_state == Running
+                    && skipped_frames < user_interface.state.frame_skip
+                {
+                    skipped_frames += 1;
+                } else {
+                    skipped_frames = 0;
+
+                    user_interface.update_framebuffer(&cpu);
+                    user_interface.render_ui(&display, &mut cpu);
+                }
+            }
+
+            Event::LoopDestroyed => {
+                let vsync = user_interface.state.vsync;
+
+                // The geometry is unknown while fullscreen, so the last session's is kept.
+                let config = WindowConfig::capture(display.gl_window().window(), vsync)
+                    .or_else(|| saved_window.map(|config| WindowConfig { vsync, ..config }));
+
+                if let Some(config) = config {
+                    config.save();
+                }
+
+                user_interface.state.save_flag_registers(&cpu);
             }
 
             Event::RedrawEventsCleared => {
-                fps_limiter.limit();
+                if context_vsync {
+                    fps_limiter.resync();
+                } else {
+                    fps_limiter.limit();
+                }
             }
 
             Event::WindowEvent { event, .. } => match event {
@@ -132,10 +506,15 @@ pub fn start() {
                     *control_flow = ControlFlow::Exit;
                 }
 
-                WindowEvent::KeyboardInput { ref input, .. }
-                    if user_interface.state.emulator_state == gui::EmulatorState::Running =>
-                {
-                    handle_keyboard_event(&mut cpu, input);
+                WindowEvent::KeyboardInput { ref input, .. } => {
+                    // Keys typed into a text field, e.g. a watch expression, aren't hotkeys.
+                    if !user_interface.wants_text_input() {
+                        handle_hotkey(&mut user_interface.state, input);
+                    }
+
+                    if user_interface.state.emulator_state == gui::EmulatorState::Running {
+                        handle_keyboard_event(&mut cpu, user_interface.state.key_map, input);
+                    }
                 }
 
                 _ => {}
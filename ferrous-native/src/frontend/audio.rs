@@ -1,17 +1,172 @@
-use std::sync::mpsc::{channel, Sender};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
-use rodio::{source::SineWave, OutputStream, Sink};
+use rodio::{OutputStream, Sink, Source};
+
+/// Sample rate of the generated beep.
+const SAMPLE_RATE: u32 = 48000;
+
+/// Frequency of the beep in Hz.
+const FREQUENCY: f32 = 420.0;
+
+/// Time for the beep to fade out when emulation is paused or reset, and
+/// back in when it is resumed. Unlike the envelope, this isn't configurable,
+/// it only has to be long enough not to click.
+const PAUSE_FADE: Duration = Duration::from_millis(20);
+
+/// Parameters shared between the frontend and the audio thread.
+struct Shared {
+    /// Should the beep be playing?
+    gate: AtomicBool,
+
+    /// Time to ramp up to full volume, in microseconds.
+    attack: AtomicU32,
+
+    /// Time to ramp down to silence, in microseconds.
+    release: AtomicU32,
+
+    /// Stereo position of the beep, as the bits of an `f32` from -1 to 1.
+    pan: AtomicU32,
+
+    /// Is emulation paused? A beep held through the pause is faded out.
+    paused: AtomicBool,
+
+    /// Was the beep cut off by a reset? It is then released over
+    /// at least `PAUSE_FADE`, however short the envelope's release.
+    cut: AtomicBool,
+}
+
+/// Sine wave beep, with a linear attack/release envelope
+/// so that starting and stopping it doesn't click.
+struct Beep {
+    shared: Arc<Shared>,
+
+    /// Position in the current period of the wave, from 0 to 1.
+    phase: f32,
+
+    /// Current volume of the envelope, from 0 to 1.
+    gain: f32,
+
+    /// Current volume of the pause fade, from 0 to 1.
+    volume: f32,
+
+    /// Right channel sample of the current frame, if not yet played.
+    right: Option<f32>,
+}
+
+/// Get the left and right channel gains for the given pan,
+/// where -1 is fully left, 0 is centered and 1 is fully right.
+///
+/// Centered sound plays at full volume on both channels.
+fn pan_gains(pan: f32) -> (f32, f32) {
+    let pan = pan.clamp(-1.0, 1.0);
+
+    ((1.0 - pan).min(1.0), (1.0 + pan).min(1.0))
+}
+
+/// Move the value towards the target by at most the step.
+fn ramp(value: f32, target: f32, step: f32) -> f32 {
+    if value < target {
+        (value + step).min(target)
+    } else {
+        (value - step).max(target)
+    }
+}
+
+impl Iterator for Beep {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        // Samples are interleaved, left first.
+        if let Some(right) = self.right.take() {
+            return Some(right);
+        }
+
+        let gate = self.shared.gate.load(Ordering::Relaxed);
+        let release = self.shared.release.load(Ordering::Relaxed);
+
+        let (target, ramp_time) = if gate {
+            (1.0, self.shared.attack.load(Ordering::Relaxed))
+        } else if self.shared.cut.load(Ordering::Relaxed) {
+            (0.0, release.max(PAUSE_FADE.as_micros() as u32))
+        } else {
+            (0.0, release)
+        };
+
+        // Change in gain per sample, for the ramp to take the configured time.
+        let step = if ramp_time == 0 {
+            1.0
+        } else {
+            1_000_000.0 / (ramp_time as f32 * SAMPLE_RATE as f32)
+        };
+
+        self.gain = ramp(self.gain, target, step);
+
+        // The cut is over once the beep has faded out.
+        if !gate && self.gain == 0.0 {
+            self.shared.cut.store(false, Ordering::Relaxed);
+        }
+
+        let paused = self.shared.paused.load(Ordering::Relaxed);
+        let fade_step = 1.0 / (PAUSE_FADE.as_secs_f32() * SAMPLE_RATE as f32);
+
+        self.volume = ramp(self.volume, if paused { 0.0 } else { 1.0 }, fade_step);
+
+        let sample = (self.phase * std::f32::consts::TAU).sin() * self.gain * self.volume;
+        self.phase = (self.phase + FREQUENCY / SAMPLE_RATE as f32).fract();
+
+        let pan = f32::from_bits(self.shared.pan.load(Ordering::Relaxed));
+        let (left, right) = pan_gains(pan);
+
+        self.right = Some(sample * right);
+
+        Some(sample * left)
+    }
+}
+
+impl Source for Beep {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        2
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
 
 /// Audio subsystem for the interpreter.
 pub struct Audio {
-    sender: Sender<bool>,
+    shared: Arc<Shared>,
 }
 
 impl Audio {
     /// Create a new `Audio` instance.
     pub fn new() -> Self {
-        let source = SineWave::new(420);
-        let (tx, rx) = channel();
+        let shared = Arc::new(Shared {
+            gate: AtomicBool::new(false),
+            attack: AtomicU32::new(5000),
+            release: AtomicU32::new(5000),
+            pan: AtomicU32::new(0.0f32.to_bits()),
+            paused: AtomicBool::new(false),
+            cut: AtomicBool::new(false),
+        });
+
+        let source = Beep {
+            shared: Arc::clone(&shared),
+            phase: 0.0,
+            gain: 0.0,
+            volume: 1.0,
+            right: None,
+        };
 
         // We are going for a multithreaded model due to a conflict with glium.
         // See [https://github.com/RustAudio/rodio/issues/214] for more.
@@ -19,28 +174,120 @@ impl Audio {
             let (_stream, stream_handle) = OutputStream::try_default().unwrap();
             let sink = Sink::try_new(&stream_handle).unwrap();
 
-            sink.pause();
+            // The beep never ends, it is silenced by its envelope instead.
             sink.append(source);
-
-            while let Ok(continue_beep) = rx.recv() {
-                if continue_beep {
-                    sink.play();
-                } else {
-                    sink.pause();
-                }
-            }
+            sink.sleep_until_end();
         });
 
-        Audio { sender: tx }
+        Audio { shared }
     }
 
     /// Start playing the beep, if not already playing.
     pub fn play_beep(&self) {
-        self.sender.send(true).unwrap();
+        self.shared.gate.store(true, Ordering::Relaxed);
     }
 
     /// Pause the beep, if not already paused.
     pub fn pause_beep(&self) {
-        self.sender.send(false).unwrap();
+        self.shared.gate.store(false, Ordering::Relaxed);
+    }
+
+    /// Fade the beep out when emulation is paused, and back in when it is
+    /// resumed, rather than cutting it off. The beep itself keeps playing,
+    /// so a tone held through the pause is heard again after it.
+    pub fn set_paused(&self, paused: bool) {
+        self.shared.paused.store(paused, Ordering::Relaxed);
+    }
+
+    /// Stop the beep because emulation was reset, fading it out
+    /// like a pause does, rather than cutting it off with a click.
+    pub fn cut_beep(&self) {
+        self.shared.gate.store(false, Ordering::Relaxed);
+        self.shared.cut.store(true, Ordering::Relaxed);
+    }
+
+    /// Set how long the beep takes to fade in and out.
+    pub fn set_envelope(&self, attack: Duration, release: Duration) {
+        self.shared
+            .attack
+            .store(attack.as_micros() as u32, Ordering::Relaxed);
+        self.shared
+            .release
+            .store(release.as_micros() as u32, Ordering::Relaxed);
+    }
+
+    /// Set the stereo position of the beep,
+    /// from -1 (fully left) to 1 (fully right).
+    pub fn set_pan(&self, pan: f32) {
+        self.shared.pan.store(pan.to_bits(), Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    use super::{pan_gains, ramp, Beep, Shared, PAUSE_FADE, SAMPLE_RATE};
+
+    #[test]
+    fn centered_pan_plays_both_channels_fully() {
+        assert_eq!(pan_gains(0.0), (1.0, 1.0));
+    }
+
+    #[test]
+    fn hard_pan_silences_the_other_channel() {
+        assert_eq!(pan_gains(-1.0), (1.0, 0.0));
+        assert_eq!(pan_gains(1.0), (0.0, 1.0));
+    }
+
+    #[test]
+    fn partial_pan_attenuates_the_other_channel() {
+        assert_eq!(pan_gains(0.25), (0.75, 1.0));
+        assert_eq!(pan_gains(-0.5), (1.0, 0.5));
+    }
+
+    #[test]
+    fn ramps_stop_at_the_target() {
+        assert_eq!(ramp(0.0, 1.0, 0.25), 0.25);
+        assert_eq!(ramp(0.9, 1.0, 0.25), 1.0);
+        assert_eq!(ramp(0.5, 0.0, 0.25), 0.25);
+        assert_eq!(ramp(0.1, 0.0, 0.25), 0.0);
+    }
+
+    #[test]
+    fn a_cut_beep_fades_out_despite_an_instant_release() {
+        let shared = Arc::new(Shared {
+            gate: AtomicBool::new(false),
+            attack: AtomicU32::new(0),
+            release: AtomicU32::new(0),
+            pan: AtomicU32::new(0.0f32.to_bits()),
+            paused: AtomicBool::new(false),
+            cut: AtomicBool::new(true),
+        });
+
+        let mut beep = Beep {
+            shared: Arc::clone(&shared),
+            phase: 0.0,
+            gain: 1.0,
+            volume: 1.0,
+            right: None,
+        };
+
+        beep.nth(1);
+        assert!(beep.gain > 0.9);
+
+        // Two samples per frame, left and right.
+        let frames = (PAUSE_FADE.as_secs_f32() * SAMPLE_RATE as f32) as usize;
+        beep.nth(frames * 2);
+
+        assert_eq!(beep.gain, 0.0);
+        assert!(!shared.cut.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn pan_is_clamped() {
+        assert_eq!(pan_gains(3.0), (0.0, 1.0));
+        assert_eq!(pan_gains(-3.0), (1.0, 0.0));
     }
 }
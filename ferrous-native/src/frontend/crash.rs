@@ -0,0 +1,52 @@
+//! Contains a panic hook that saves the interpreter state,
+//! so that a crashed session can be reloaded and inspected.
+
+use std::cell::RefCell;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+thread_local! {
+    /// Interpreter state at the start of the last emulated frame.
+    static LAST_STATE: RefCell<Option<ferrous::Ferrous>> = RefCell::new(None);
+}
+
+/// Install a panic hook, that writes the last recorded state
+/// to `crash-<timestamp>.fc8` before unwinding.
+pub fn install_hook() {
+    let default_hook = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+
+        LAST_STATE.with(|state| {
+            let state = match state.try_borrow() {
+                Ok(state) => state,
+                Err(_) => return,
+            };
+
+            if let Some(cpu) = state.as_ref() {
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0);
+                let path = format!("crash-{}.fc8", timestamp);
+
+                match serde_json::to_vec(cpu) {
+                    Ok(serialized) if std::fs::write(&path, serialized).is_ok() => {
+                        info!("saved crash state to {}.", path);
+                    }
+
+                    _ => warn!("failed to save crash state."),
+                }
+            }
+        });
+    }));
+}
+
+/// Record the given state, to be saved if a panic occurs.
+pub fn record_state(cpu: &ferrous::Ferrous) {
+    LAST_STATE.with(|state| {
+        if let Ok(mut state) = state.try_borrow_mut() {
+            *state = Some(cpu.clone());
+        }
+    });
+}
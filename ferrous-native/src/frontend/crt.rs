@@ -0,0 +1,153 @@
+//! Contains an optional CRT shader, used when
+//! drawing the interpreter's screen.
+
+use glium::{
+    implement_vertex, index::NoIndices, index::PrimitiveType, uniform,
+    uniforms::MagnifySamplerFilter, DrawParameters, Program, Rect, Surface, Texture2d,
+    VertexBuffer,
+};
+
+const VERTEX_SHADER: &str = r#"
+    #version 140
+
+    in vec2 position;
+    in vec2 tex_coords;
+
+    out vec2 v_tex_coords;
+
+    void main() {
+        v_tex_coords = tex_coords;
+        gl_Position = vec4(position, 0.0, 1.0);
+    }
+"#;
+
+const FRAGMENT_SHADER: &str = r#"
+    #version 140
+
+    in vec2 v_tex_coords;
+    out vec4 color;
+
+    uniform sampler2D tex;
+    uniform vec2 resolution;
+    uniform float intensity;
+
+    // Bend the texture coordinates outwards, like the glass of a CRT.
+    vec2 barrel(vec2 uv) {
+        vec2 centered = uv * 2.0 - 1.0;
+        centered *= 1.0 + dot(centered, centered) * 0.08 * intensity;
+
+        return centered * 0.5 + 0.5;
+    }
+
+    void main() {
+        vec2 uv = barrel(v_tex_coords);
+
+        if (uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+            color = vec4(0.0, 0.0, 0.0, 1.0);
+            return;
+        }
+
+        vec2 texel = 1.0 / resolution;
+        vec3 base = texture(tex, uv).rgb;
+
+        // Cheap bloom, by bleeding in the neighbouring pixels.
+        vec3 bloom = (
+            texture(tex, uv + vec2(texel.x, 0.0)).rgb +
+            texture(tex, uv - vec2(texel.x, 0.0)).rgb +
+            texture(tex, uv + vec2(0.0, texel.y)).rgb +
+            texture(tex, uv - vec2(0.0, texel.y)).rgb
+        ) * 0.25;
+
+        // Darken the edges of every row, brightest at its center.
+        float scanline = 0.5 - 0.5 * cos(uv.y * resolution.y * 6.2831853);
+
+        vec3 result = base + bloom * 0.35 * intensity;
+        result *= mix(1.0, 0.6 + 0.4 * scanline, intensity);
+
+        color = vec4(result, 1.0);
+    }
+"#;
+
+#[derive(Clone, Copy)]
+struct Vertex {
+    position: [f32; 2],
+    tex_coords: [f32; 2],
+}
+
+implement_vertex!(Vertex, position, tex_coords);
+
+/// Draws a texture to a full-screen quad through the CRT shader.
+pub struct CrtRenderer {
+    /// Compiled CRT shader program.
+    program: Program,
+
+    /// Vertices of the full-screen quad.
+    vertex_buffer: VertexBuffer<Vertex>,
+}
+
+impl CrtRenderer {
+    /// Create a new `CrtRenderer` instance.
+    pub fn new(display: &glium::Display) -> Self {
+        let program = Program::from_source(display, VERTEX_SHADER, FRAGMENT_SHADER, None)
+            .expect("Failed to compile the CRT shader.");
+
+        let vertex_buffer = VertexBuffer::new(
+            display,
+            &[
+                Vertex {
+                    position: [-1.0, -1.0],
+                    tex_coords: [0.0, 0.0],
+                },
+                Vertex {
+                    position: [1.0, -1.0],
+                    tex_coords: [1.0, 0.0],
+                },
+                Vertex {
+                    position: [-1.0, 1.0],
+                    tex_coords: [0.0, 1.0],
+                },
+                Vertex {
+                    position: [1.0, 1.0],
+                    tex_coords: [1.0, 1.0],
+                },
+            ],
+        )
+        .expect("Failed to create the CRT vertex buffer.");
+
+        Self {
+            program,
+            vertex_buffer,
+        }
+    }
+
+    /// Draw the texture into the viewport of the target,
+    /// with the given effect intensity from 0.0 to 1.0.
+    pub fn draw<S: Surface>(
+        &self,
+        target: &mut S,
+        texture: &Texture2d,
+        viewport: Rect,
+        intensity: f32,
+    ) {
+        let uniforms = uniform! {
+            tex: texture.sampled().magnify_filter(MagnifySamplerFilter::Nearest),
+            resolution: [texture.width() as f32, texture.height() as f32],
+            intensity: intensity,
+        };
+
+        let parameters = DrawParameters {
+            viewport: Some(viewport),
+            ..Default::default()
+        };
+
+        target
+            .draw(
+                &self.vertex_buffer,
+                NoIndices(PrimitiveType::TriangleStrip),
+                &self.program,
+                &uniforms,
+                &parameters,
+            )
+            .expect("Failed to draw with the CRT shader.");
+    }
+}
@@ -0,0 +1,58 @@
+//! Contains the Super Chip flag registers kept for each ROM, in a
+//! `<rom>.ferrous.flags` file next to the ROM, as some games store
+//! their high scores in them.
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// Get the path of the flag registers file for the given ROM.
+pub fn path_for(rom: &Path) -> PathBuf {
+    let mut path = OsString::from(rom);
+    path.push(".ferrous.flags");
+
+    PathBuf::from(path)
+}
+
+/// Load the flag registers kept for the given ROM.
+///
+/// Returns `None` if there are none, or the file is malformed.
+pub fn load(rom: &Path) -> Option<[u8; 8]> {
+    let data = std::fs::read(path_for(rom)).ok()?;
+
+    if data.len() != 8 {
+        warn!("ignoring malformed flag registers file.");
+        return None;
+    }
+
+    let mut flags = [0; 8];
+    flags.copy_from_slice(&data);
+
+    Some(flags)
+}
+
+/// Keep the flag registers for the given ROM, if they changed,
+/// so that no file is written for ROMs that never use them.
+pub fn save(rom: &Path, flags: &[u8; 8]) {
+    if load(rom).unwrap_or([0; 8]) == *flags {
+        return;
+    }
+
+    if std::fs::write(path_for(rom), flags).is_err() {
+        warn!("failed to save the flag registers.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::path_for;
+
+    #[test]
+    fn flags_are_kept_next_to_the_rom() {
+        assert_eq!(
+            path_for(Path::new("roms/car.ch8")),
+            Path::new("roms/car.ch8.ferrous.flags")
+        );
+    }
+}
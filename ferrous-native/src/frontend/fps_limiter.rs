@@ -1,5 +1,11 @@
 use std::time::{Duration, Instant};
 
+/// Duration of a single frame at 60 FPS.
+const FRAME_TIME: Duration = Duration::from_nanos(16_666_667);
+
+/// Default for the number of frames the limiter may fall behind.
+pub const MAX_CATCHUP_FRAMES: u32 = 4;
+
 /// Limits frames per second of the emulator to 60.
 pub struct FpsLimiter {
     /// The time the last frame occured.
@@ -7,28 +13,69 @@ pub struct FpsLimiter {
 
     /// The time the next frame should occur.
     next_frame: Instant,
+
+    /// Number of frames the limiter may fall behind, and then
+    /// catch up on by not sleeping. Frames past it are dropped.
+    max_catchup_frames: u32,
 }
 
 impl FpsLimiter {
     /// Create a new `FpsLimiter` instance.
     pub fn new() -> Self {
-        let now = Instant::now();
+        Self::starting_at(Instant::now())
+    }
 
+    /// Create a new `FpsLimiter` instance, as if the last frame occured at the given time.
+    fn starting_at(now: Instant) -> Self {
         Self {
             last_frame: now,
-            next_frame: now + Duration::from_secs_f64(1.0 / 60.0),
+            next_frame: now + FRAME_TIME,
+            max_catchup_frames: MAX_CATCHUP_FRAMES,
         }
     }
 
+    /// Set the number of frames the limiter may fall behind, e.g. after
+    /// the OS stopped the app while the window was dragged. Without a cap,
+    /// every frame missed would be run back to back, freezing the UI.
+    pub fn set_max_catchup_frames(&mut self, frames: u32) {
+        self.max_catchup_frames = frames;
+    }
+
     /// Update the frame times, and return delta.
+    ///
+    /// The delta is capped to the frames the limiter may catch up on,
+    /// so that a long stall doesn't run a second's worth of cycles at once.
     pub fn update(&mut self) -> Duration {
-        let now = Instant::now();
-        let delta = now - self.last_frame;
+        self.update_at(Instant::now())
+    }
+
+    /// Update the frame times as of the given time, and return delta.
+    fn update_at(&mut self, now: Instant) -> Duration {
+        let delta = now.saturating_duration_since(self.last_frame);
 
-        self.next_frame += Duration::from_secs_f64(1.0 / 60.0);
+        self.next_frame += FRAME_TIME;
         self.last_frame = now;
 
-        delta
+        // Drop the frames past the cap, instead of catching up on them.
+        if let Some(earliest) = now.checked_sub(FRAME_TIME * self.max_catchup_frames) {
+            self.next_frame = self.next_frame.max(earliest);
+        }
+
+        delta.min(FRAME_TIME * self.max_catchup_frames.saturating_add(1))
+    }
+
+    /// Get the number of whole frames the limiter is behind at the given time.
+    #[cfg(test)]
+    fn frames_behind(&self, now: Instant) -> u32 {
+        (now.saturating_duration_since(self.next_frame).as_nanos() / FRAME_TIME.as_nanos()) as u32
+    }
+
+    /// Set the next frame time relative to now, without sleeping.
+    ///
+    /// Used when frames are paced by V-Sync, so that the limiter doesn't
+    /// try to catch up once it is used again.
+    pub fn resync(&mut self) {
+        self.next_frame = Instant::now() + FRAME_TIME;
     }
 
     /// Limit the FPS by sleeping till targetted next frame time.
@@ -40,3 +87,43 @@ impl FpsLimiter {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_long_stall_is_capped() {
+        let start = Instant::now();
+        let mut limiter = FpsLimiter::starting_at(start);
+
+        let now = start + Duration::from_secs(1);
+        let delta = limiter.update_at(now);
+
+        assert!(limiter.frames_behind(now) <= MAX_CATCHUP_FRAMES);
+        assert_eq!(delta, FRAME_TIME * (MAX_CATCHUP_FRAMES + 1));
+    }
+
+    #[test]
+    fn the_largest_cap_does_not_overflow() {
+        let start = Instant::now();
+        let mut limiter = FpsLimiter::starting_at(start);
+        limiter.set_max_catchup_frames(u32::MAX);
+
+        let delta = Duration::from_secs(1);
+
+        assert_eq!(limiter.update_at(start + delta), delta);
+    }
+
+    #[test]
+    fn frames_on_time_are_unaffected() {
+        let start = Instant::now();
+        let mut limiter = FpsLimiter::starting_at(start);
+
+        let now = start + FRAME_TIME;
+
+        assert_eq!(limiter.update_at(now), FRAME_TIME);
+        assert_eq!(limiter.frames_behind(now), 0);
+        assert_eq!(limiter.next_frame, start + FRAME_TIME * 2);
+    }
+}
@@ -1,18 +1,91 @@
 //! Contains implementations for UIs with help
 //! of Dear ImGui.
 
+use std::cmp::Reverse;
 use std::io::prelude::*;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use glium::glutin::event::Event;
-use glium::{texture::RawImage2d, uniforms::MagnifySamplerFilter, BlitTarget, Surface, Texture2d};
+use glium::{
+    texture::RawImage2d, uniforms::MagnifySamplerFilter, BlitTarget, Rect, Surface, Texture2d,
+};
 use imgui::{
-    im_str, ColorEdit, FontConfig, FontId, FontSource, ImString, MenuItem, Slider, SliderFlags, Ui,
-    Window,
+    im_str, ColorEdit, ComboBox, FontConfig, FontId, FontSource, ImString, Key, MenuItem,
+    MouseButton, ProgressBar, Selectable, Slider, SliderFlags, Ui, Window,
 };
 
+use super::crt::CrtRenderer;
+use super::flags;
+use super::key_map::KeyMap;
+use super::palette::{self, Palette};
+use super::profile::Profile;
+use super::sidecar::{parse_hex_color, RomConfig};
+use super::watch::Watch;
+
 const EMULATOR_VERSION: &str = env!("CARGO_PKG_VERSION");
 const FONT_SOURCE: &[u8] = include_bytes!("../assets/FiraMono.ttf");
 
+/// Small ROMs bundled with the emulator, so that there is
+/// something to run without having to find ROM files.
+const BUILTIN_ROMS: [(&str, &[u8]); 3] = [
+    ("Splash", include_bytes!("../assets/roms/splash.ch8")),
+    ("Keypad Test", include_bytes!("../assets/roms/keypad.ch8")),
+    ("Bounce", include_bytes!("../assets/roms/bounce.ch8")),
+];
+
+/// Permitted range of CPU cycles executed per frame.
+pub const CYCLES_RANGE: RangeInclusive<u16> = 1..=2000;
+
+/// Permitted range of instructions executed per second.
+const IPS_RANGE: RangeInclusive<u32> = 60..=120_000;
+
+/// Permitted range of frames skipped between rendered frames.
+const FRAME_SKIP_RANGE: RangeInclusive<u8> = 0..=5;
+
+/// Permitted range of the beep's attack and release times, in milliseconds.
+const ENVELOPE_RANGE: RangeInclusive<u16> = 0..=100;
+
+/// Hue rotations per second of the rainbow mode.
+const RAINBOW_SPEED: f32 = 0.25;
+
+/// How long a toast stays on screen.
+const TOAST_DURATION: Duration = Duration::from_millis(1500);
+
+/// Number of instructions shown in the disassembly window.
+const DISASSEMBLY_LENGTH: usize = 32;
+
+/// Number of instructions shown before the program counter, in the disassembly window.
+const DISASSEMBLY_CONTEXT: usize = 4;
+
+/// Bytes of memory shown in each row of the memory map.
+const MEMORY_MAP_ROW: usize = 64;
+
+/// Size of a single byte's cell in the memory map, in pixels.
+const MEMORY_MAP_CELL: f32 = 4.0;
+
+/// Size of a single sprite pixel in the sprite viewer, in pixels.
+const SPRITE_VIEWER_CELL: f32 = 12.0;
+
+/// Width of the bars in the opcode histogram, in pixels.
+const HISTOGRAM_BAR_WIDTH: f32 = 160.0;
+
+/// Memory regions shown in the memory map's legend.
+const MEMORY_REGIONS: [(ferrous::MemoryRegion, &str); 5] = [
+    (ferrous::MemoryRegion::LowResFont, "Low-res Font"),
+    (ferrous::MemoryRegion::HighResFont, "High-res Font"),
+    (ferrous::MemoryRegion::Reserved, "Reserved"),
+    (ferrous::MemoryRegion::Program, "Program"),
+    (ferrous::MemoryRegion::Data, "Data"),
+];
+
+/// Number of frames shown in the frame time graph.
+const FRAME_TIME_HISTORY: usize = 120;
+
+/// Maximum number of manual edits that can be undone.
+const EDIT_HISTORY_LIMIT: usize = 256;
+
 #[derive(Clone, Copy, PartialEq)]
 pub enum EmulatorState {
     Idle,
@@ -21,6 +94,15 @@ pub enum EmulatorState {
     Quit,
 }
 
+/// A manual edit made in the memory editor.
+struct MemoryEdit {
+    /// Address of the edited byte.
+    address: usize,
+
+    /// Value of the byte before the edit.
+    old_value: u8,
+}
+
 /// Stores the UserInterface state.
 pub struct State {
     /// Is about window currently open?
@@ -29,6 +111,12 @@ pub struct State {
     /// Is metrics window currently open?
     metrics_window: bool,
 
+    /// Is frame time window currently open?
+    frame_time_window: bool,
+
+    /// Durations of the most recent frames in milliseconds, oldest first.
+    frame_times: Vec<f32>,
+
     /// FontId of the larger sized font.
     big_font: FontId,
 
@@ -47,26 +135,302 @@ pub struct State {
     /// Are debug controls active.
     debug_controls: bool,
 
+    /// Is the mode overlay active.
+    mode_overlay: bool,
+
+    /// Is the memory map active.
+    debug_memory_map: bool,
+
+    /// Is the watch window active.
+    debug_watch_view: bool,
+
+    /// Is the disassembly window active.
+    debug_disassembly_view: bool,
+
+    /// Is the sprite viewer active.
+    debug_sprite_view: bool,
+
+    /// Is the opcode histogram active.
+    debug_opcode_histogram: bool,
+
+    /// Height of the sprite shown in the sprite viewer, as the n of Dxyn.
+    sprite_height: u8,
+
+    /// Was the interpreter stepped from the debugger this frame?
+    stepped: bool,
+
+    /// Was the interpreter reset this frame, e.g. by Reset, loading a ROM or
+    /// loading a state? A beep cut off by it is faded out, rather than released.
+    pub was_reset: bool,
+
+    /// Expressions shown in the watch window.
+    watches: Vec<Watch>,
+
+    /// Expression being typed in the watch window.
+    watch_input: ImString,
+
     /// ImGui Memory Editor widget.
     memory_edit: imgui_memory_editor::MemoryEditor,
 
     /// CPU cycles to execute frame.
     pub cycles_per_frame: u16,
 
+    /// Should the speed be set in instructions per second,
+    /// instead of a fixed number of cycles per frame?
+    pub ips_mode: bool,
+
+    /// Instructions to execute per second, when in IPS mode.
+    pub instructions_per_second: u32,
+
+    /// CPU cycles executed in the last 60 Hz tick.
+    pub frame_cycles: u16,
+
+    /// Frames emulated without rendering, after each rendered frame.
+    pub frame_skip: u8,
+
+    /// Time for the beep to fade in, in milliseconds.
+    pub beep_attack: u16,
+
+    /// Time for the beep to fade out, in milliseconds.
+    pub beep_release: u16,
+
+    /// Stereo position of the beep, from -1 (left) to 1 (right).
+    pub beep_pan: f32,
+
     /// Current state of the CPU.
     pub emulator_state: EmulatorState,
 
+    /// Keyboard layout of the keypad.
+    pub key_map: KeyMap,
+
     /// Foreground color.
     fg_color: [f32; 3],
 
     /// Background color.
     bg_color: [f32; 3],
 
+    /// Is the foreground color cycling through hues?
+    rainbow: bool,
+
+    /// Current hue of the rainbow mode, from 0.0 to 1.0.
+    rainbow_hue: f32,
+
+    /// Palettes applied, imported or exported recently, most recent first.
+    recent_palettes: Vec<Palette>,
+
+    /// Is the CRT shader used to draw the screen?
+    crt_shader: bool,
+
+    /// Intensity of the CRT shader effects, from 0.0 to 1.0.
+    crt_intensity: f32,
+
+    /// Is the screen upscaled with linear filtering, instead of nearest neighbour?
+    smooth_scaling: bool,
+
+    /// Is the screen only upscaled by whole multiples?
+    integer_scaling: bool,
+
     /// Height of the main menu bar.
     menu_height: Option<u32>,
 
     /// Is a ROM currently loaded?
-    rom_loaded: bool,
+    pub rom_loaded: bool,
+
+    /// Should frames be paced by the display's V-Sync instead of the FPS limiter?
+    /// Takes effect after a restart, as it is fixed when the GL context is created.
+    pub vsync: bool,
+
+    /// Should the interpreter be reset when a ROM is opened?
+    /// If not, only the ROM's bytes are replaced.
+    reset_on_load: bool,
+
+    /// Short message shown on screen, and the time it was shown at.
+    toast: Option<(String, Instant)>,
+
+    /// Path of the currently loaded ROM, if it was loaded from a file.
+    rom_path: Option<PathBuf>,
+
+    /// ROM configuration as it was last loaded or saved.
+    rom_config: Option<RomConfig>,
+
+    /// Manual edits that can be undone, the most recent one last.
+    edit_history: Vec<MemoryEdit>,
+}
+
+impl State {
+    /// Capture the current per-ROM settings.
+    fn current_rom_config(&self, cpu: &ferrous::Ferrous) -> RomConfig {
+        RomConfig {
+            load_store_mode: cpu.load_store_mode,
+            shift_quirk: cpu.shift_quirk,
+            jump_quirk: cpu.jump_quirk,
+            logic_quirk: cpu.logic_quirk,
+            wrap_x: cpu.wrap_x,
+            wrap_y: cpu.wrap_y,
+            key_release_quirk: cpu.key_release_quirk,
+            cycles_per_frame: self.cycles_per_frame,
+            ips_mode: self.ips_mode,
+            instructions_per_second: self.instructions_per_second,
+            fg_color: self.fg_color,
+            bg_color: self.bg_color,
+        }
+    }
+
+    /// Apply the given per-ROM settings.
+    fn apply_rom_config(&mut self, config: &RomConfig, cpu: &mut ferrous::Ferrous) {
+        cpu.set_load_store_mode(config.load_store_mode);
+        cpu.set_shift(config.shift_quirk);
+        cpu.set_jump(config.jump_quirk);
+        cpu.set_logic(config.logic_quirk);
+        cpu.set_wrap(config.wrap_x, config.wrap_y);
+        cpu.set_key_release(config.key_release_quirk);
+
+        self.cycles_per_frame = config.cycles_per_frame;
+        self.ips_mode = config.ips_mode;
+        self.instructions_per_second = config.instructions_per_second;
+        self.fg_color = config.fg_color;
+        self.bg_color = config.bg_color;
+    }
+
+    /// Capture every setting, for exporting them as a profile.
+    fn current_profile(&self, cpu: &ferrous::Ferrous) -> Profile {
+        Profile {
+            rom_config: self.current_rom_config(cpu),
+            frame_skip: self.frame_skip,
+            beep_attack: self.beep_attack,
+            beep_release: self.beep_release,
+            beep_pan: self.beep_pan,
+            key_map: self.key_map,
+            ..Profile::default()
+        }
+    }
+
+    /// Apply every setting of the given profile.
+    fn apply_profile(&mut self, profile: &Profile, cpu: &mut ferrous::Ferrous) {
+        self.apply_rom_config(&profile.rom_config, cpu);
+
+        self.frame_skip = profile.frame_skip;
+        self.beep_attack = profile.beep_attack;
+        self.beep_release = profile.beep_release;
+        self.beep_pan = profile.beep_pan;
+        self.key_map = profile.key_map;
+    }
+
+    /// Get the path of the currently loaded ROM, if it was loaded from a file.
+    #[cfg_attr(not(feature = "hot-reload"), allow(dead_code))]
+    pub fn rom_path(&self) -> Option<&Path> {
+        self.rom_path.as_deref()
+    }
+
+    /// Treat the current settings as saved, so that they
+    /// are not written to the ROM's sidecar file.
+    pub fn sync_rom_config(&mut self, cpu: &ferrous::Ferrous) {
+        if self.rom_path.is_some() {
+            self.rom_config = Some(self.current_rom_config(cpu));
+        }
+    }
+
+    /// Keep the flag registers for the ROM file that is loaded, if any,
+    /// before another ROM or state replaces them.
+    pub fn save_flag_registers(&self, cpu: &ferrous::Ferrous) {
+        if let Some(path) = &self.rom_path {
+            flags::save(path, cpu.flag_registers());
+        }
+    }
+
+    /// Write the current settings to the ROM's sidecar file,
+    /// if they changed since they were last loaded or saved.
+    fn save_rom_config(&mut self, cpu: &ferrous::Ferrous) {
+        if let Some(path) = &self.rom_path {
+            let config = self.current_rom_config(cpu);
+
+            if self.rom_config.as_ref() != Some(&config) {
+                config.save(path);
+                self.rom_config = Some(config);
+            }
+        }
+    }
+
+    /// Record the bytes changed by the memory editor.
+    fn record_memory_edits(&mut self, before: &[u8], after: &[u8]) {
+        for (address, (old, new)) in before.iter().zip(after).enumerate() {
+            if old != new {
+                self.edit_history.push(MemoryEdit {
+                    address,
+                    old_value: *old,
+                });
+            }
+        }
+
+        if self.edit_history.len() > EDIT_HISTORY_LIMIT {
+            let excess = self.edit_history.len() - EDIT_HISTORY_LIMIT;
+            self.edit_history.drain(..excess);
+        }
+    }
+
+    /// Revert the most recent manual edit.
+    fn undo_edit(&mut self, cpu: &mut ferrous::Ferrous) {
+        if let Some(edit) = self.edit_history.pop() {
+            cpu.ram[edit.address] = edit.old_value;
+        }
+    }
+
+    /// Add the duration of the last frame to the frame time graph.
+    fn record_frame_time(&mut self, delta: Duration) {
+        if self.frame_times.len() == FRAME_TIME_HISTORY {
+            self.frame_times.remove(0);
+        }
+
+        self.frame_times.push(delta.as_secs_f32() * 1000.0);
+    }
+
+    /// Get the instructions to execute per second, in either speed mode.
+    ///
+    /// Cycles per frame are executed on each 60 Hz tick, whatever
+    /// the rate the screen is drawn at, see `ferrous::TickClock`.
+    pub fn effective_ips(&self) -> u32 {
+        if self.ips_mode {
+            self.instructions_per_second
+        } else {
+            self.cycles_per_frame as u32 * ferrous::TICK_RATE
+        }
+    }
+
+    /// Set the foreground color from a `#RRGGBB` string.
+    pub fn set_fg_hex(&mut self, hex: &str) -> Result<(), &'static str> {
+        self.fg_color = parse_hex_color(hex)?;
+
+        Ok(())
+    }
+
+    /// Set the background color from a `#RRGGBB` string.
+    pub fn set_bg_hex(&mut self, hex: &str) -> Result<(), &'static str> {
+        self.bg_color = parse_hex_color(hex)?;
+
+        Ok(())
+    }
+
+    /// Use the given palette's colors, and remember it as recently used.
+    fn apply_palette(&mut self, palette: Palette) {
+        self.fg_color = palette.fg_color;
+        self.bg_color = palette.bg_color;
+
+        palette::push_recent(&mut self.recent_palettes, palette);
+    }
+
+    /// Get the foreground color, following the rainbow mode if it is on.
+    fn current_fg_color(&self) -> [f32; 3] {
+        if self.rainbow {
+            hsv_to_rgb(self.rainbow_hue, 1.0, 1.0)
+        } else {
+            self.fg_color
+        }
+    }
+
+    /// Briefly show a message on screen.
+    pub fn show_toast(&mut self, message: String) {
+        self.toast = Some((message, Instant::now()));
+    }
 }
 
 /// Implementation of the UI with Dear ImGui.
@@ -81,7 +445,10 @@ pub struct UserInterface {
     platform: imgui_winit_support::WinitPlatform,
 
     /// RGB framebuffer.
-    framebuffer: Box<[u8; 128 * 64 * 3]>,
+    framebuffer: Box<[u8; ferrous::VRAM_SIZE * 3]>,
+
+    /// CRT shader renderer.
+    crt: CrtRenderer,
 
     /// Ui State
     pub state: State,
@@ -132,16 +499,34 @@ impl UserInterface {
             imgui,
             renderer,
             platform,
-            framebuffer: Box::new([0; 128 * 64 * 3]),
+            framebuffer: Box::new([0; ferrous::VRAM_SIZE * 3]),
+            crt: CrtRenderer::new(display),
             state: State {
                 menu_height: None,
                 about_window: false,
                 metrics_window: false,
+                frame_time_window: false,
+                frame_times: Vec::with_capacity(FRAME_TIME_HISTORY),
                 cycles_per_frame: 10,
+                ips_mode: false,
+                instructions_per_second: 600,
+                frame_cycles: 10,
+                frame_skip: 0,
+                beep_attack: 5,
+                beep_release: 5,
+                beep_pan: 0.0,
                 emulator_state: EmulatorState::Idle,
+                key_map: KeyMap::Cosmac,
                 big_font,
                 fg_color: [1.0; 3],
                 bg_color: [0.0; 3],
+                rainbow: false,
+                rainbow_hue: 0.0,
+                recent_palettes: palette::load_recent(),
+                crt_shader: false,
+                crt_intensity: 0.5,
+                smooth_scaling: false,
+                integer_scaling: false,
                 rom_loaded: false,
                 palette_window: false,
                 debug_memory_view: false,
@@ -149,38 +534,41 @@ impl UserInterface {
                 debug_stack_view: false,
                 debug_register_view: false,
                 debug_controls: false,
+                mode_overlay: false,
+                debug_memory_map: false,
+                debug_watch_view: false,
+                debug_disassembly_view: false,
+                debug_sprite_view: false,
+                debug_opcode_histogram: false,
+                sprite_height: 5,
+                stepped: false,
+                was_reset: false,
+                watches: Vec::new(),
+                watch_input: ImString::with_capacity(64),
+                vsync: false,
+                reset_on_load: true,
+                toast: None,
+                rom_path: None,
+                rom_config: None,
+                edit_history: Vec::new(),
             },
         }
     }
 
     /// Update the framebuffer, with new data.
     pub fn update_framebuffer(&mut self, cpu: &ferrous::Ferrous) {
-        let data = cpu.get_video_buffer();
-
-        let fg = self
-            .state
-            .fg_color
-            .iter()
-            .map(|x| ((*x) * 255.0).round() as u8)
-            .collect::<Vec<u8>>();
-
-        let bg = self
-            .state
-            .bg_color
-            .iter()
-            .map(|x| ((*x) * 255.0).round() as u8)
-            .collect::<Vec<u8>>();
-
-        self.framebuffer
-            .chunks_exact_mut(3)
-            .enumerate()
-            .for_each(|(i, rgb)| {
-                if data[i] == 0 {
-                    rgb.copy_from_slice(&bg);
-                } else {
-                    rgb.copy_from_slice(&fg);
-                }
-            });
+        if self.state.rainbow {
+            // Complete one full rotation of the hue every few seconds.
+            let delta = self.imgui.io().delta_time;
+            self.state.rainbow_hue = (self.state.rainbow_hue + delta * RAINBOW_SPEED).fract();
+        }
+
+        fill_framebuffer(
+            &mut self.framebuffer[..],
+            cpu.get_video_buffer(),
+            self.state.current_fg_color(),
+            self.state.bg_color,
+        );
     }
 
     /// Let Dear ImGui platform handle window events.
@@ -191,8 +579,15 @@ impl UserInterface {
             .handle_event(self.imgui.io_mut(), gl_window.window(), event);
     }
 
+    /// Is a text field being edited, so that keys are typed into it
+    /// rather than acting as hotkeys?
+    pub fn wants_text_input(&self) -> bool {
+        self.imgui.io().want_text_input
+    }
+
     pub fn update_delta(&mut self, delta: std::time::Duration) {
         self.imgui.io_mut().update_delta_time(delta);
+        self.state.record_frame_time(delta);
     }
 
     pub fn prepare_frame(&mut self, display: &glium::Display) {
@@ -211,6 +606,23 @@ impl UserInterface {
         render_menu(&mut self.state, &mut ui, cpu);
         render_windows(&mut self.state, &mut ui, cpu);
 
+        // The framebuffer was filled before the debugger stepped,
+        // refill it so that the step shows up on this frame.
+        if std::mem::take(&mut self.state.stepped) {
+            fill_framebuffer(
+                &mut self.framebuffer[..],
+                cpu.get_video_buffer(),
+                self.state.current_fg_color(),
+                self.state.bg_color,
+            );
+        }
+
+        // Wait until the slider or color picker being dragged is released,
+        // rather than writing the sidecar file on every frame it changes.
+        if !ui.is_any_item_active() {
+            self.state.save_rom_config(cpu);
+        }
+
         self.platform.prepare_render(&ui, gl_window.window());
 
         let mut target = display.draw();
@@ -228,20 +640,40 @@ impl UserInterface {
         let texture = Texture2d::new(display, image).unwrap();
         let window_size = gl_window.window().inner_size();
 
-        texture.as_surface().blit_whole_color_to(
-            &target,
-            &BlitTarget {
-                left: 0,
-                bottom: 0,
-                width: window_size.width as i32,
-                height: (window_size
-                    .height
-                    .saturating_sub(self.state.menu_height.unwrap_or(0)))
-                    as i32,
-            },
-            MagnifySamplerFilter::Nearest,
+        let screen_height = window_size
+            .height
+            .saturating_sub(self.state.menu_height.unwrap_or(0));
+
+        let screen = fit_screen(
+            window_size.width,
+            screen_height,
+            width as u32,
+            height as u32,
+            self.state.integer_scaling,
         );
 
+        let filter = if self.state.smooth_scaling {
+            MagnifySamplerFilter::Linear
+        } else {
+            MagnifySamplerFilter::Nearest
+        };
+
+        if self.state.crt_shader {
+            self.crt
+                .draw(&mut target, &texture, screen, self.state.crt_intensity);
+        } else {
+            texture.as_surface().blit_whole_color_to(
+                &target,
+                &BlitTarget {
+                    left: screen.left,
+                    bottom: screen.bottom,
+                    width: screen.width as i32,
+                    height: screen.height as i32,
+                },
+                filter,
+            );
+        }
+
         let draw_data = ui.render();
         self.renderer
             .render(&mut target, draw_data)
@@ -251,6 +683,186 @@ impl UserInterface {
     }
 }
 
+/// Get the largest area of the window that the screen can be drawn to,
+/// keeping its aspect ratio, centered in the space below the menu bar.
+///
+/// With integer scaling, the scale is rounded down to a whole number,
+/// unless the window is too small to fit the screen at its native size.
+fn fit_screen(
+    window_width: u32,
+    window_height: u32,
+    width: u32,
+    height: u32,
+    integer_scaling: bool,
+) -> Rect {
+    let mut scale = (window_width as f32 / width as f32).min(window_height as f32 / height as f32);
+
+    if integer_scaling && scale >= 1.0 {
+        scale = scale.floor();
+    }
+
+    let fitted_width = (width as f32 * scale) as u32;
+    let fitted_height = (height as f32 * scale) as u32;
+
+    Rect {
+        left: (window_width - fitted_width) / 2,
+        bottom: (window_height - fitted_height) / 2,
+        width: fitted_width,
+        height: fitted_height,
+    }
+}
+
+/// Fill the RGB framebuffer from the interpreter's video buffer.
+fn fill_framebuffer(framebuffer: &mut [u8], data: &[u8], fg_color: [f32; 3], bg_color: [f32; 3]) {
+    let fg = fg_color.map(|x| (x * 255.0).round() as u8);
+    let bg = bg_color.map(|x| (x * 255.0).round() as u8);
+
+    framebuffer
+        .chunks_exact_mut(3)
+        .zip(data)
+        .for_each(|(rgb, &pixel)| {
+            if pixel == 0 {
+                rgb.copy_from_slice(&bg);
+            } else {
+                rgb.copy_from_slice(&fg);
+            }
+        });
+}
+
+/// Get the pixels of the sprite Dxyn would draw from the given bytes, row by row.
+///
+/// Wide sprites are 16 pixels, two bytes, per row.
+fn sprite_pixels(bytes: &[u8], wide: bool) -> Vec<Vec<bool>> {
+    let row_bytes = if wide { 2 } else { 1 };
+
+    bytes
+        .chunks(row_bytes)
+        .map(|row| {
+            row.iter()
+                .flat_map(|byte| (0..8).rev().map(move |bit| byte & (1 << bit) != 0))
+                .collect()
+        })
+        .collect()
+}
+
+/// Convert a HSV color to RGB, all components range from 0.0 to 1.0.
+fn hsv_to_rgb(h: f32, s: f32, v: f32) -> [f32; 3] {
+    let sector = h * 6.0;
+    let f = sector.fract();
+
+    let p = v * (1.0 - s);
+    let q = v * (1.0 - s * f);
+    let t = v * (1.0 - s * (1.0 - f));
+
+    match sector as u32 % 6 {
+        0 => [v, t, p],
+        1 => [q, v, p],
+        2 => [p, v, t],
+        3 => [p, q, v],
+        4 => [t, p, v],
+        _ => [v, p, q],
+    }
+}
+
+/// Reset the interpreter and load the ROM at the given path,
+/// applying the settings from its sidecar file if there is one.
+///
+/// Returns `false` if the ROM can't be read or doesn't fit in memory,
+/// which is logged and shown as a toast, leaving the interpreter as it was.
+pub fn load_rom_file(state: &mut State, cpu: &mut ferrous::Ferrous, path: &Path) -> bool {
+    let loaded = crate::rom_file::read(path)
+        .map_err(|error| error.to_string())
+        .and_then(|data| load_rom_data(state, cpu, &data).map_err(String::from));
+
+    if let Err(error) = loaded {
+        report_load_error(state, path, &error);
+        return false;
+    }
+
+    if let Some(config) = RomConfig::load(path) {
+        state.apply_rom_config(&config, cpu);
+    }
+
+    if let Some(flags) = flags::load(path) {
+        cpu.set_flag_registers(&flags);
+    }
+
+    state.rom_path = Some(path.to_path_buf());
+    state.sync_rom_config(cpu);
+
+    info!("loaded ROM {} ({} bytes).", path.display(), cpu.rom_len());
+
+    true
+}
+
+/// Log that the file at the given path couldn't be loaded, and show it as a toast.
+fn report_load_error(state: &mut State, path: &Path, error: &str) {
+    warn!("failed to load {}: {}", path.display(), error);
+    state.show_toast(format!("Failed to load: {}", error));
+}
+
+/// Load the given ROM, which doesn't have a sidecar file.
+///
+/// Returns an `Err` if it doesn't fit in memory, leaving the interpreter as it was.
+fn load_rom_data(
+    state: &mut State,
+    cpu: &mut ferrous::Ferrous,
+    data: &[u8],
+) -> Result<(), &'static str> {
+    if data.len() > cpu.ram.len() - cpu.entry_point() {
+        return Err("ROM is too large to fit in memory.");
+    }
+
+    // The previous ROM's flag registers mustn't leak into this one.
+    state.save_flag_registers(cpu);
+    cpu.set_flag_registers(&[0; 8]);
+
+    if state.reset_on_load {
+        state.was_reset = true;
+
+        cpu.reset();
+        cpu.load_rom(data)
+    } else {
+        cpu.reload_rom(data)
+    }?;
+
+    let features = ferrous::Ferrous::detect_feature_set(data);
+
+    if features.xo_chip {
+        state.show_toast("This ROM uses unsupported XO-Chip features.".to_string());
+    } else if features.super_chip {
+        state.show_toast("This ROM uses Super Chip features.".to_string());
+    }
+
+    state.rom_loaded = true;
+    state.rom_path = None;
+    state.edit_history.clear();
+
+    Ok(())
+}
+
+/// Get the color a memory region is shown with in the memory map.
+fn region_color(region: ferrous::MemoryRegion) -> [f32; 4] {
+    match region {
+        ferrous::MemoryRegion::LowResFont => [0.95, 0.55, 0.25, 1.0],
+        ferrous::MemoryRegion::HighResFont => [0.95, 0.85, 0.3, 1.0],
+        ferrous::MemoryRegion::Reserved => [0.4, 0.4, 0.4, 1.0],
+        ferrous::MemoryRegion::Program => [0.45, 0.7, 1.0, 1.0],
+        ferrous::MemoryRegion::Data => [0.4, 0.9, 0.45, 1.0],
+    }
+}
+
+/// Get the color an instruction category is highlighted with.
+fn category_color(category: ferrous::Category) -> [f32; 4] {
+    match category {
+        ferrous::Category::Flow => [0.95, 0.55, 0.25, 1.0],
+        ferrous::Category::Load => [0.45, 0.7, 1.0, 1.0],
+        ferrous::Category::Arithmetic => [0.75, 0.55, 1.0, 1.0],
+        ferrous::Category::Draw => [0.4, 0.9, 0.45, 1.0],
+        ferrous::Category::Io => [0.95, 0.85, 0.3, 1.0],
+    }
+}
+
 /// Construct a cell for register values.
 fn register_cell(ui: &Ui, name: String, value: String) {
     ui.align_text_to_frame_padding();
@@ -274,37 +886,100 @@ fn render_menu(state: &mut State, ui: &mut Ui, cpu: &mut ferrous::Ferrous) {
             // I know it's ugly. It really is.
             if MenuItem::new(im_str!("Open")).build(ui) {
                 if let Ok(nfd2::Response::Okay(path)) =
-                    nfd2::open_file_dialog(Some("ch8,c8,fc8"), None)
+                    nfd2::open_file_dialog(Some("ch8,c8,gz,fc8"), None)
                 {
                     state.emulator_state = EmulatorState::Idle;
 
                     let is_correct_extension =
                         path.extension() == Some(&std::ffi::OsStr::new("fc8"));
-                    let data = std::fs::read(path).expect("Failed to read ROM file.");
 
                     if is_correct_extension {
-                        let sav: ferrous::Ferrous = serde_json::from_slice(&data)
-                            .expect("Could not deserialize JSON input.");
-
-                        let _ = std::mem::replace(cpu, sav);
+                        let sav = std::fs::read(&path)
+                            .map_err(|error| error.to_string())
+                            .and_then(|data| {
+                                serde_json::from_slice::<ferrous::Ferrous>(&data)
+                                    .map_err(|error| error.to_string())
+                            });
+
+                        match sav {
+                            Ok(sav) => {
+                                state.save_flag_registers(cpu);
+                                let _ = std::mem::replace(cpu, sav);
+
+                                state.rom_loaded = true;
+                                state.rom_path = None;
+                                state.edit_history.clear();
+                                state.was_reset = true;
+
+                                info!("loaded state from {}.", path.display());
+                            }
+
+                            Err(error) => report_load_error(state, &path, &error),
+                        }
                     } else {
-                        cpu.reset();
-                        cpu.load_rom(&data)
-                            .expect("Failed to load ROM in interpreter memory.");
+                        load_rom_file(state, cpu, &path);
                     }
+                }
+            }
 
-                    state.rom_loaded = true;
+            if let Some(builtin_menu) = ui.begin_menu(im_str!("Load Built-in ROM"), true) {
+                for (name, data) in BUILTIN_ROMS.iter() {
+                    if MenuItem::new(&ImString::new(*name)).build(ui) {
+                        match load_rom_data(state, cpu, data) {
+                            Ok(()) => {
+                                info!("loaded built-in ROM {}.", name);
+                                state.emulator_state = EmulatorState::Running;
+                            }
+
+                            Err(error) => warn!("failed to load built-in ROM {}: {}", name, error),
+                        }
+                    }
+                }
+
+                builtin_menu.end(ui);
+            }
+
+            MenuItem::new(im_str!("Reset on Load")).build_with_ref(ui, &mut state.reset_on_load);
+
+            // Exports the program as it currently is in memory, including self-modifications.
+            if MenuItem::new(im_str!("Export ROM"))
+                .enabled(state.rom_loaded)
+                .build(ui)
+            {
+                if let Ok(nfd2::Response::Okay(path)) = nfd2::open_save_dialog(Some("ch8"), None) {
+                    if std::fs::write(path, cpu.rom_bytes()).is_err() {
+                        warn!("failed to export the ROM.");
+                    }
+                }
+            }
+
+            if MenuItem::new(im_str!("Import Profile")).build(ui) {
+                if let Ok(nfd2::Response::Okay(path)) = nfd2::open_file_dialog(Some("json"), None) {
+                    match Profile::load(&path) {
+                        Ok(profile) => state.apply_profile(&profile, cpu),
+                        Err(error) => warn!("failed to import profile: {}.", error),
+                    }
+                }
+            }
+
+            if MenuItem::new(im_str!("Export Profile")).build(ui) {
+                if let Ok(nfd2::Response::Okay(path)) = nfd2::open_save_dialog(Some("json"), None) {
+                    if let Err(error) = state.current_profile(cpu).save(&path) {
+                        warn!("failed to export profile: {}.", error);
+                    }
                 }
             }
 
             if MenuItem::new(im_str!("Save State")).build(ui) {
                 if let Ok(nfd2::Response::Okay(path)) = nfd2::open_save_dialog(Some("fc8"), None) {
                     let mut file =
-                        std::fs::File::create(path).expect("Failed to create save file.");
+                        std::fs::File::create(&path).expect("Failed to create save file.");
                     let serialized = serde_json::to_vec(cpu).expect("Failed to serialize CPU.");
 
                     file.write_all(&serialized)
                         .expect("Failed to write save file.");
+
+                    info!("saved state to {}.", path.display());
                 }
             }
 
@@ -330,38 +1005,203 @@ fn render_menu(state: &mut State, ui: &mut Ui, cpu: &mut ferrous::Ferrous) {
                 state.emulator_state = EmulatorState::Paused;
             }
 
+            // Restart the current ROM from scratch, staying paused if it was.
             if MenuItem::new(im_str!("Reset"))
+                .enabled(state.rom_loaded)
+                .build(ui)
+            {
+                if let Err(error) = cpu.reset_to_rom() {
+                    warn!("failed to reload the ROM: {}", error);
+                }
+
+                state.edit_history.clear();
+                state.was_reset = true;
+            }
+
+            if MenuItem::new(im_str!("Eject"))
                 .enabled(state.emulator_state != EmulatorState::Idle)
                 .build(ui)
             {
+                state.save_flag_registers(cpu);
                 cpu.reset();
 
                 state.rom_loaded = false;
+                state.rom_path = None;
+                state.edit_history.clear();
+                state.was_reset = true;
                 state.emulator_state = EmulatorState::Idle;
             }
 
             MenuItem::new(im_str!("Palette")).build_with_ref(ui, &mut state.palette_window);
+            MenuItem::new(im_str!("V-Sync")).build_with_ref(ui, &mut state.vsync);
+
+            if ui.is_item_hovered() {
+                ui.tooltip_text("Takes effect after a restart.");
+            }
+
+            if let Some(scaling_menu) = ui.begin_menu(im_str!("Scaling"), true) {
+                MenuItem::new(im_str!("Smooth")).build_with_ref(ui, &mut state.smooth_scaling);
+                MenuItem::new(im_str!("Integer")).build_with_ref(ui, &mut state.integer_scaling);
+
+                scaling_menu.end(&ui);
+            }
+
+            if let Some(crt_menu) = ui.begin_menu(im_str!("CRT Shader"), true) {
+                MenuItem::new(im_str!("Enabled")).build_with_ref(ui, &mut state.crt_shader);
+
+                Slider::<f32>::new(im_str!("intensity"))
+                    .range(0.0..=1.0)
+                    .flags(SliderFlags::ALWAYS_CLAMP)
+                    .build(&ui, &mut state.crt_intensity);
+
+                crt_menu.end(&ui);
+            }
+
+            if let Some(key_map_menu) = ui.begin_menu(im_str!("Keyboard Layout"), true) {
+                for (key_map, name) in KeyMap::ALL {
+                    if MenuItem::new(&ImString::new(name))
+                        .selected(state.key_map == key_map)
+                        .build(ui)
+                    {
+                        // Keys held with the old layout would otherwise stay pressed.
+                        cpu.reset_keys();
+                        state.key_map = key_map;
+                    }
+                }
+
+                key_map_menu.end(ui);
+            }
+
+            let mut input_queue = cpu.input_queue;
+
+            if MenuItem::new(im_str!("Input Queue")).build_with_ref(ui, &mut input_queue) {
+                cpu.set_input_queue(input_queue);
+            }
+
+            let mut skip_delays = cpu.skip_delays;
+
+            if MenuItem::new(im_str!("Skip Delays")).build_with_ref(ui, &mut skip_delays) {
+                cpu.set_skip_delays(skip_delays);
+            }
 
             if let Some(cycles_menu) = ui.begin_menu(im_str!("Cycles per Frame"), true) {
                 Slider::<u16>::new(im_str!("cycles"))
-                    .range(1..=2000)
+                    .range(CYCLES_RANGE)
                     .flags(SliderFlags::ALWAYS_CLAMP)
                     .build(&ui, &mut state.cycles_per_frame);
 
                 cycles_menu.end(&ui);
             }
 
+            if let Some(ips_menu) = ui.begin_menu(im_str!("Instructions per Second"), true) {
+                MenuItem::new(im_str!("Enabled")).build_with_ref(ui, &mut state.ips_mode);
+
+                Slider::<u32>::new(im_str!("ips"))
+                    .range(IPS_RANGE)
+                    .flags(SliderFlags::ALWAYS_CLAMP)
+                    .build(&ui, &mut state.instructions_per_second);
+
+                ui.text(format!("Cycles per tick: {}", state.frame_cycles));
+
+                ips_menu.end(&ui);
+            }
+
+            if let Some(frame_skip_menu) = ui.begin_menu(im_str!("Frame Skip"), true) {
+                Slider::<u8>::new(im_str!("frames"))
+                    .range(FRAME_SKIP_RANGE)
+                    .flags(SliderFlags::ALWAYS_CLAMP)
+                    .build(&ui, &mut state.frame_skip);
+
+                frame_skip_menu.end(&ui);
+            }
+
+            if let Some(beep_menu) = ui.begin_menu(im_str!("Beep"), true) {
+                Slider::<u16>::new(im_str!("attack (ms)"))
+                    .range(ENVELOPE_RANGE)
+                    .flags(SliderFlags::ALWAYS_CLAMP)
+                    .build(&ui, &mut state.beep_attack);
+
+                Slider::<u16>::new(im_str!("release (ms)"))
+                    .range(ENVELOPE_RANGE)
+                    .flags(SliderFlags::ALWAYS_CLAMP)
+                    .build(&ui, &mut state.beep_release);
+
+                Slider::<f32>::new(im_str!("pan"))
+                    .range(-1.0..=1.0)
+                    .flags(SliderFlags::ALWAYS_CLAMP)
+                    .build(&ui, &mut state.beep_pan);
+
+                beep_menu.end(&ui);
+            }
+
             if let Some(quirks_menu) = ui.begin_menu(im_str!("Quirks"), true) {
-                MenuItem::new(im_str!("Load and Store Quirk"))
-                    .build_with_ref(ui, &mut cpu.load_store_quirk);
+                if let Some(load_store_menu) = ui.begin_menu(im_str!("Load and Store"), true) {
+                    use ferrous::LoadStoreMode::*;
+
+                    for (label, mode) in [
+                        (im_str!("Increment by X + 1"), IncrementByXPlus1),
+                        (im_str!("Increment by X"), IncrementByX),
+                        (im_str!("Unchanged"), Unchanged),
+                    ] {
+                        if MenuItem::new(label)
+                            .selected(cpu.load_store_mode == mode)
+                            .build(ui)
+                        {
+                            cpu.set_load_store_mode(mode);
+                        }
+                    }
+
+                    load_store_menu.end(ui);
+                }
 
                 MenuItem::new(im_str!("Shift Quirk")).build_with_ref(ui, &mut cpu.shift_quirk);
 
                 MenuItem::new(im_str!("Jump Quirk")).build_with_ref(ui, &mut cpu.jump_quirk);
 
+                MenuItem::new(im_str!("Logic Quirk")).build_with_ref(ui, &mut cpu.logic_quirk);
+
+                MenuItem::new(im_str!("Wrap Sprites Horizontally"))
+                    .build_with_ref(ui, &mut cpu.wrap_x);
+
+                MenuItem::new(im_str!("Wrap Sprites Vertically"))
+                    .build_with_ref(ui, &mut cpu.wrap_y);
+
+                let mut key_release = cpu.key_release_quirk;
+
+                if MenuItem::new(im_str!("Key Release Quirk")).build_with_ref(ui, &mut key_release)
+                {
+                    cpu.set_key_release(key_release);
+                }
+
                 quirks_menu.end(ui);
             }
 
+            if MenuItem::new(im_str!("Force High-Res"))
+                .selected(cpu.is_highres)
+                .build(ui)
+            {
+                cpu.set_high_res(!cpu.is_highres);
+            }
+
+            if let Some(policy_menu) = ui.begin_menu(im_str!("Invalid Opcodes"), true) {
+                use ferrous::InvalidOpcodePolicy::*;
+
+                for (label, policy) in [
+                    (im_str!("Ignore"), Ignore),
+                    (im_str!("Halt"), Halt),
+                    (im_str!("Panic"), Panic),
+                ] {
+                    if MenuItem::new(label)
+                        .selected(cpu.invalid_opcode_policy == policy)
+                        .build(ui)
+                    {
+                        cpu.set_invalid_opcode_policy(policy);
+                    }
+                }
+
+                policy_menu.end(ui);
+            }
+
             emulation_menu.end(ui);
         }
 
@@ -370,6 +1210,25 @@ fn render_menu(state: &mut State, ui: &mut Ui, cpu: &mut ferrous::Ferrous) {
             MenuItem::new(im_str!("Registers")).build_with_ref(ui, &mut state.debug_register_view);
             MenuItem::new(im_str!("Address Stack")).build_with_ref(ui, &mut state.debug_stack_view);
             MenuItem::new(im_str!("Memory")).build_with_ref(ui, &mut state.debug_memory_view);
+            MenuItem::new(im_str!("Memory Map")).build_with_ref(ui, &mut state.debug_memory_map);
+            MenuItem::new(im_str!("Watches")).build_with_ref(ui, &mut state.debug_watch_view);
+            MenuItem::new(im_str!("Disassembly"))
+                .build_with_ref(ui, &mut state.debug_disassembly_view);
+            MenuItem::new(im_str!("Sprite Viewer"))
+                .build_with_ref(ui, &mut state.debug_sprite_view);
+            MenuItem::new(im_str!("Opcode Histogram"))
+                .build_with_ref(ui, &mut state.debug_opcode_histogram);
+            MenuItem::new(im_str!("Mode Overlay")).build_with_ref(ui, &mut state.mode_overlay);
+
+            ui.separator();
+
+            if MenuItem::new(im_str!("Undo Edit"))
+                .shortcut(im_str!("Ctrl+Z"))
+                .enabled(!state.edit_history.is_empty())
+                .build(ui)
+            {
+                state.undo_edit(cpu);
+            }
 
             debug_menu.end(ui);
         }
@@ -378,6 +1237,8 @@ fn render_menu(state: &mut State, ui: &mut Ui, cpu: &mut ferrous::Ferrous) {
             MenuItem::new(im_str!("Dear ImGui Metrics"))
                 .build_with_ref(ui, &mut state.metrics_window);
 
+            MenuItem::new(im_str!("Frame Times")).build_with_ref(ui, &mut state.frame_time_window);
+
             MenuItem::new(im_str!("About")).build_with_ref(ui, &mut state.about_window);
 
             help_menu.end(ui);
@@ -390,6 +1251,13 @@ fn render_menu(state: &mut State, ui: &mut Ui, cpu: &mut ferrous::Ferrous) {
 
 /// Render additional windows, like about, metrics etc..
 fn render_windows(state: &mut State, ui: &mut Ui, cpu: &mut ferrous::Ferrous) {
+    // Undo the last manual edit, unless a text field is being edited.
+    let io = ui.io();
+
+    if io.key_ctrl && !io.want_text_input && ui.is_key_pressed(Key::Z) {
+        state.undo_edit(cpu);
+    }
+
     if state.about_window {
         let font_id = state.big_font;
 
@@ -415,6 +1283,37 @@ fn render_windows(state: &mut State, ui: &mut Ui, cpu: &mut ferrous::Ferrous) {
         ui.show_metrics_window(&mut state.metrics_window);
     }
 
+    if state.frame_time_window {
+        let frame_times = &state.frame_times;
+
+        Window::new(im_str!("Frame Times"))
+            .always_auto_resize(true)
+            .resizable(false)
+            .opened(&mut state.frame_time_window)
+            .build(ui, || {
+                if frame_times.is_empty() {
+                    ui.text_disabled(im_str!("No frames yet."));
+                    return;
+                }
+
+                let min = frame_times.iter().copied().fold(f32::INFINITY, f32::min);
+                let max = frame_times.iter().copied().fold(0.0, f32::max);
+                let avg = frame_times.iter().sum::<f32>() / frame_times.len() as f32;
+
+                ui.text(format!(
+                    "Min: {:.2} ms  Avg: {:.2} ms  Max: {:.2} ms",
+                    min, avg, max
+                ));
+
+                // Scaled to at least two 60 Hz frames, so that small jitter looks small.
+                ui.plot_lines(im_str!("##frame_times"), frame_times)
+                    .graph_size([360.0, 80.0])
+                    .scale_min(0.0)
+                    .scale_max(max.max(2000.0 / 60.0))
+                    .build();
+            });
+    }
+
     if state.palette_window {
         if let Some(window) = Window::new(im_str!("Palette"))
             .always_auto_resize(true)
@@ -434,18 +1333,237 @@ fn render_windows(state: &mut State, ui: &mut Ui, cpu: &mut ferrous::Ferrous) {
                 .alpha(false)
                 .build(&ui);
 
+            ui.checkbox(im_str!("Rainbow"), &mut state.rainbow);
+
+            ui.separator();
+
+            let mut chosen = None;
+
+            if let Some(combo) = ComboBox::new(im_str!("Presets"))
+                .preview_value(im_str!("Choose a Palette"))
+                .begin(ui)
+            {
+                let built_in = Palette::built_in();
+
+                // Recently used palettes are listed first.
+                for palette in state.recent_palettes.iter().chain(built_in.iter()) {
+                    if Selectable::new(&ImString::new(&palette.name)).build(ui) {
+                        chosen = Some(palette.clone());
+                    }
+                }
+
+                combo.end(ui);
+            }
+
+            if ui.button(im_str!("Import"), [100.0, 20.0]) {
+                if let Ok(nfd2::Response::Okay(path)) = nfd2::open_file_dialog(Some("json"), None) {
+                    match Palette::load(&path) {
+                        Ok(palette) => chosen = Some(palette),
+                        Err(error) => warn!("failed to import palette: {}.", error),
+                    }
+                }
+            }
+
+            ui.same_line(0.0);
+
+            if ui.button(im_str!("Export"), [100.0, 20.0]) {
+                if let Ok(nfd2::Response::Okay(path)) = nfd2::open_save_dialog(Some("json"), None) {
+                    // The palette is named after the file it is exported to.
+                    let name = path
+                        .file_stem()
+                        .map_or("Palette".into(), |stem| stem.to_string_lossy());
+
+                    let palette = Palette {
+                        name: name.into_owned(),
+                        fg_color: state.fg_color,
+                        bg_color: state.bg_color,
+                    };
+
+                    match palette.save(&path) {
+                        Ok(()) => palette::push_recent(&mut state.recent_palettes, palette),
+                        Err(error) => warn!("failed to export palette: {}.", error),
+                    }
+                }
+            }
+
+            if let Some(palette) = chosen {
+                state.apply_palette(palette);
+            }
+
             window.end(&ui);
         }
     }
 
     if state.debug_memory_view {
+        let before = cpu.ram;
+
         state
             .memory_edit
             .draw_window(ui, im_str!("Memory"), cpu.ram.as_mut(), None);
 
+        state.record_memory_edits(&before, &cpu.ram);
+
         state.debug_memory_view = state.memory_edit.get_open();
     }
 
+    if state.debug_sprite_view {
+        if let Some(window) = Window::new(im_str!("Sprite Viewer"))
+            .always_auto_resize(true)
+            .resizable(false)
+            .opened(&mut state.debug_sprite_view)
+            .begin(ui)
+        {
+            Slider::<u8>::new(im_str!("n"))
+                .range(0..=15)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut state.sprite_height);
+
+            ui.text_disabled("A height of 0 is a 16x16 sprite.");
+
+            let wide = state.sprite_height == 0;
+            let pixels = sprite_pixels(&cpu.read_sprite(state.sprite_height as usize), wide);
+            let width = if wide { 16 } else { 8 };
+
+            ui.text(format!("I = {:#05X}, {}x{}", cpu.id, width, pixels.len()));
+
+            let size = [
+                width as f32 * SPRITE_VIEWER_CELL,
+                pixels.len() as f32 * SPRITE_VIEWER_CELL,
+            ];
+
+            let origin = ui.cursor_screen_pos();
+            ui.invisible_button(im_str!("sprite"), size);
+
+            let [fg, bg] = [state.current_fg_color(), state.bg_color];
+            let draw_list = ui.get_window_draw_list();
+
+            for (row, line) in pixels.iter().enumerate() {
+                for (column, &lit) in line.iter().enumerate() {
+                    let x = origin[0] + column as f32 * SPRITE_VIEWER_CELL;
+                    let y = origin[1] + row as f32 * SPRITE_VIEWER_CELL;
+                    let [r, g, b] = if lit { fg } else { bg };
+
+                    draw_list
+                        .add_rect(
+                            [x, y],
+                            [x + SPRITE_VIEWER_CELL, y + SPRITE_VIEWER_CELL],
+                            [r, g, b, 1.0],
+                        )
+                        .filled(true)
+                        .build();
+                }
+            }
+
+            // Only one draw list can be borrowed at a time.
+            drop(draw_list);
+
+            window.end(ui);
+        }
+    }
+
+    if state.debug_opcode_histogram {
+        Window::new(im_str!("Opcode Histogram"))
+            .always_auto_resize(true)
+            .resizable(false)
+            .opened(&mut state.debug_opcode_histogram)
+            .build(ui, || {
+                let mut histogram: Vec<_> = cpu.opcode_histogram().into_iter().collect();
+
+                if histogram.is_empty() {
+                    ui.text_disabled(im_str!("No code found."));
+                    return;
+                }
+
+                histogram.sort_by_key(|&(_, count)| Reverse(count));
+
+                let most = histogram[0].1 as f32;
+
+                ui.text_disabled("Instructions reachable from the entry point.");
+
+                for (mnemonic, count) in histogram {
+                    ProgressBar::new(count as f32 / most)
+                        .size([HISTOGRAM_BAR_WIDTH, 0.0])
+                        .overlay_text(&ImString::new(count.to_string()))
+                        .build(ui);
+
+                    ui.same_line(0.0);
+                    ui.text(mnemonic);
+                }
+            });
+    }
+
+    if state.debug_memory_map {
+        Window::new(im_str!("Memory Map"))
+            .always_auto_resize(true)
+            .resizable(false)
+            .opened(&mut state.debug_memory_map)
+            .build(ui, || {
+                for (region, name) in MEMORY_REGIONS.iter() {
+                    ui.text_colored(region_color(*region), name);
+                }
+
+                ui.separator();
+                ui.text("PC and I are marked in white,");
+                ui.text("zero bytes are dimmed.");
+
+                if cpu.has_self_modified() {
+                    ui.text_colored([0.95, 0.55, 0.25, 1.0], "The program has modified itself.");
+                }
+
+                let rows = cpu.ram.len() / MEMORY_MAP_ROW;
+                let size = [
+                    MEMORY_MAP_ROW as f32 * MEMORY_MAP_CELL,
+                    rows as f32 * MEMORY_MAP_CELL,
+                ];
+
+                let origin = ui.cursor_screen_pos();
+                ui.invisible_button(im_str!("memory_map"), size);
+
+                let draw_list = ui.get_window_draw_list();
+
+                for address in 0..cpu.ram.len() {
+                    let x = origin[0] + (address % MEMORY_MAP_ROW) as f32 * MEMORY_MAP_CELL;
+                    let y = origin[1] + (address / MEMORY_MAP_ROW) as f32 * MEMORY_MAP_CELL;
+
+                    let color = if address == cpu.pc || address == cpu.id {
+                        [1.0; 4]
+                    } else {
+                        region_color(cpu.memory_region(address))
+                    };
+
+                    // Dim unused bytes, so that the layout of the data stands out.
+                    let color = if cpu.ram[address] == 0 {
+                        [color[0] * 0.4, color[1] * 0.4, color[2] * 0.4, 1.0]
+                    } else {
+                        color
+                    };
+
+                    draw_list
+                        .add_rect([x, y], [x + MEMORY_MAP_CELL, y + MEMORY_MAP_CELL], color)
+                        .filled(true)
+                        .build();
+                }
+
+                // Only one draw list can be borrowed at a time.
+                drop(draw_list);
+
+                if ui.is_item_hovered() {
+                    let [mouse_x, mouse_y] = ui.io().mouse_pos;
+
+                    let column = ((mouse_x - origin[0]) / MEMORY_MAP_CELL) as usize;
+                    let row = ((mouse_y - origin[1]) / MEMORY_MAP_CELL) as usize;
+                    let address = (row * MEMORY_MAP_ROW + column).min(cpu.ram.len() - 1);
+
+                    ui.tooltip_text(format!(
+                        "{:#05X}: {:#04X} ({:?})",
+                        address,
+                        cpu.ram[address],
+                        cpu.memory_region(address)
+                    ));
+                }
+            });
+    }
+
     if state.debug_stack_view {
         Window::new(im_str!("Address Stack"))
             .size([240.0, 270.0], imgui::Condition::Always)
@@ -470,7 +1588,7 @@ fn render_windows(state: &mut State, ui: &mut Ui, cpu: &mut ferrous::Ferrous) {
 
     if state.debug_register_view {
         Window::new(im_str!("Registers"))
-            .size([235.0, 300.0], imgui::Condition::Always)
+            .size([235.0, 390.0], imgui::Condition::Always)
             .resizable(false)
             .opened(&mut state.debug_register_view)
             .build(ui, || {
@@ -492,9 +1610,199 @@ fn render_windows(state: &mut State, ui: &mut Ui, cpu: &mut ferrous::Ferrous) {
                         ui.next_column();
                     }
                 }
+
+                // Super Chip Flag Registers.
+                ui.next_column();
+                ui.separator();
+
+                for (i, v) in cpu.flag_registers().iter().enumerate() {
+                    register_cell(ui, format!("R{}  ", i), format!("{:#04X}", *v));
+
+                    if i == 3 {
+                        ui.next_column();
+                    }
+                }
             });
     }
 
+    if state.debug_watch_view {
+        let watches = &mut state.watches;
+        let watch_input = &mut state.watch_input;
+
+        Window::new(im_str!("Watches"))
+            .size([300.0, 240.0], imgui::Condition::FirstUseEver)
+            .opened(&mut state.debug_watch_view)
+            .build(ui, || {
+                let width = ui.push_item_width(200.0);
+
+                let submitted = ui
+                    .input_text(im_str!("##watch_input"), watch_input)
+                    .enter_returns_true(true)
+                    .build();
+
+                width.pop(ui);
+                ui.same_line(0.0);
+
+                if (ui.button(im_str!("Add"), [0.0, 0.0]) || submitted)
+                    && !watch_input.to_str().trim().is_empty()
+                {
+                    watches.push(Watch::new(watch_input.to_str()));
+                    watch_input.clear();
+                }
+
+                ui.separator();
+
+                let mut removed = None;
+
+                for (i, watch) in watches.iter().enumerate() {
+                    let id = ui.push_id(i as i32);
+
+                    if ui.small_button(im_str!("x")) {
+                        removed = Some(i);
+                    }
+
+                    ui.same_line(0.0);
+                    ui.text_colored([0.0, 1.0, 0.0, 1.0], &watch.source);
+                    ui.same_line(0.0);
+                    ui.text(watch.value(cpu));
+
+                    id.pop(ui);
+                }
+
+                if let Some(i) = removed {
+                    watches.remove(i);
+                }
+            });
+    }
+
+    if state.debug_disassembly_view {
+        // Address picked from a row's context menu.
+        let mut run_to = None;
+
+        Window::new(im_str!("Disassembly"))
+            .size([260.0, 520.0], imgui::Condition::FirstUseEver)
+            .opened(&mut state.debug_disassembly_view)
+            .build(ui, || {
+                // Show a few instructions before the program counter for context.
+                let window = cpu.disasm_window(
+                    DISASSEMBLY_CONTEXT,
+                    DISASSEMBLY_LENGTH - DISASSEMBLY_CONTEXT - 1,
+                );
+
+                for (address, mnemonic) in window {
+                    let opcode = match cpu.memory_slice(address..address + 2) {
+                        Some(&[high, low]) => u16::from_be_bytes([high, low]),
+                        _ => break,
+                    };
+
+                    let id = ui.push_id(address as i32);
+
+                    let marker = if cpu.is_breakpoint(address) { "*" } else { " " };
+                    let label =
+                        ImString::new(format!("{} {:#05X}  {:04X}  ", marker, address, opcode));
+
+                    Selectable::new(&label)
+                        .selected(address == cpu.pc)
+                        .build(ui);
+
+                    if ui.is_item_clicked(MouseButton::Right) {
+                        ui.open_popup(im_str!("row_menu"));
+                    }
+
+                    ui.popup(im_str!("row_menu"), || {
+                        if MenuItem::new(im_str!("Run to Here")).build(ui) {
+                            run_to = Some(address);
+                        }
+                    });
+
+                    ui.same_line(0.0);
+
+                    match ferrous::Instruction::decode(opcode) {
+                        Some(instruction) => {
+                            ui.text_colored(category_color(instruction.category()), mnemonic)
+                        }
+                        None => ui.text_disabled(mnemonic),
+                    }
+
+                    id.pop(ui);
+                }
+            });
+
+        // Run until the address is reached, stopping at any breakpoints on the way.
+        if let Some(address) = run_to {
+            if state.rom_loaded && state.emulator_state != EmulatorState::Idle {
+                cpu.set_temporary_breakpoint(address);
+                state.emulator_state = EmulatorState::Running;
+            }
+        }
+    }
+
+    if state.mode_overlay {
+        let (height, width) = cpu.get_height_width();
+        let ips = if state.emulator_state == EmulatorState::Running {
+            state.effective_ips()
+        } else {
+            0
+        };
+
+        Window::new(im_str!("Mode Overlay"))
+            .position(
+                [8.0, state.menu_height.unwrap_or(0) as f32 + 8.0],
+                imgui::Condition::Always,
+            )
+            .bg_alpha(0.35)
+            .no_decoration()
+            .always_auto_resize(true)
+            .save_settings(false)
+            .focus_on_appearing(false)
+            .no_nav()
+            .build(ui, || {
+                ui.text(format!("Mode: {}x{}", width, height));
+                ui.text(format!("IPS:  {}", ips));
+                ui.text(format!("CPF:  {}", state.frame_cycles));
+                ui.text(format!(
+                    "Beep: {}",
+                    if cpu.should_beep() { "On" } else { "Off" }
+                ));
+
+                // The instruction about to execute, peeked from memory.
+                ui.text(format!("PC:   {:#05X}", cpu.pc));
+
+                if let Some(&[high, low]) = cpu.memory_slice(cpu.pc..cpu.pc + 2) {
+                    let opcode = u16::from_be_bytes([high, low]);
+
+                    ui.same_line(0.0);
+
+                    match ferrous::Instruction::decode(opcode) {
+                        Some(instruction) => ui.text_colored(
+                            category_color(instruction.category()),
+                            instruction.to_string(),
+                        ),
+                        None => ui.text_disabled(ferrous::disassemble(opcode)),
+                    }
+                }
+            });
+    }
+
+    if let Some((message, shown_at)) = &state.toast {
+        if shown_at.elapsed() < TOAST_DURATION {
+            Window::new(im_str!("Toast"))
+                .position(
+                    [8.0, ui.io().display_size[1] - 40.0],
+                    imgui::Condition::Always,
+                )
+                .bg_alpha(0.35)
+                .no_decoration()
+                .always_auto_resize(true)
+                .save_settings(false)
+                .focus_on_appearing(false)
+                .no_nav()
+                .build(ui, || ui.text(message));
+        } else {
+            state.toast = None;
+        }
+    }
+
     if state.debug_controls {
         if let Some(token) = Window::new(im_str!("Debug Controls"))
             .resizable(false)
@@ -519,9 +1827,15 @@ fn render_windows(state: &mut State, ui: &mut Ui, cpu: &mut ferrous::Ferrous) {
             if ui.button(im_str!("Step"), [100.0, 20.0])
                 && state.rom_loaded
                 && state.emulator_state != EmulatorState::Idle
-                && cpu.execute_cycle().is_none()
+                && !cpu.is_halted
             {
-                eprintln!("[WARN] invalid or unknown opcode encountered.")
+                let pc = cpu.pc;
+
+                if cpu.execute_cycle().is_none() {
+                    super::log_failed_cycle(cpu, pc);
+                }
+
+                state.stepped = true;
             }
 
             ui.same_line(0.0);
@@ -533,6 +1847,21 @@ fn render_windows(state: &mut State, ui: &mut Ui, cpu: &mut ferrous::Ferrous) {
                 cpu.step_timers();
             }
 
+            ui.same_line(0.0);
+
+            if ui.button(im_str!("Step Frame"), [100.0, 20.0])
+                && state.rom_loaded
+                && state.emulator_state != EmulatorState::Idle
+            {
+                let cycles = (state.effective_ips() / ferrous::TICK_RATE) as u16;
+
+                if cpu.advance_frame(cycles) > 0 {
+                    warn!("invalid or unknown opcode encountered.")
+                }
+
+                state.stepped = true;
+            }
+
             ui.separator();
 
             register_cell(
@@ -0,0 +1,92 @@
+//! Contains the watcher that reloads the ROM when it changes on disk.
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::Duration;
+
+use notify::{watcher, DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+
+use super::gui;
+
+/// Time to wait for writes to settle, before reporting a change.
+const DEBOUNCE_TIME: Duration = Duration::from_millis(250);
+
+/// Watches the loaded ROM's file for changes.
+pub struct RomWatcher {
+    watcher: RecommendedWatcher,
+    receiver: Receiver<DebouncedEvent>,
+
+    /// Path of the ROM currently being watched.
+    path: Option<PathBuf>,
+}
+
+impl RomWatcher {
+    /// Create a new `RomWatcher` instance, that isn't watching anything yet.
+    pub fn new() -> Self {
+        let (sender, receiver) = channel();
+        let watcher = watcher(sender, DEBOUNCE_TIME).expect("Failed to create the ROM watcher.");
+
+        Self {
+            watcher,
+            receiver,
+            path: None,
+        }
+    }
+
+    /// Follow the ROM that is currently loaded,
+    /// and reload it if it changed on disk.
+    pub fn update(&mut self, state: &mut gui::State, cpu: &mut ferrous::Ferrous) {
+        if self.path.as_deref() != state.rom_path() {
+            self.watch(state.rom_path().map(Path::to_path_buf));
+        }
+
+        if let Some(path) = self.path.clone() {
+            // A ROM that is still being written may fail to load, it is
+            // reloaded again once the write finishes.
+            if self.has_changed(&path) && gui::load_rom_file(state, cpu, &path) {
+                state.show_toast("Reloaded ROM.".to_string());
+            }
+        }
+    }
+
+    /// Watch the given path, instead of the previous one.
+    fn watch(&mut self, path: Option<PathBuf>) {
+        if let Some(directory) = self.path.as_deref().and_then(Path::parent) {
+            let _ = self.watcher.unwatch(directory);
+        }
+
+        // The directory is watched instead of the file itself, as many
+        // editors and assemblers replace the file instead of writing to it.
+        if let Some(directory) = path.as_deref().and_then(Path::parent) {
+            if self
+                .watcher
+                .watch(directory, RecursiveMode::NonRecursive)
+                .is_err()
+            {
+                warn!("failed to watch the ROM for changes.");
+            }
+        }
+
+        self.path = path;
+    }
+
+    /// Check if the given file was written to or replaced,
+    /// since the last call.
+    fn has_changed(&self, path: &Path) -> bool {
+        let mut changed = false;
+
+        for event in self.receiver.try_iter() {
+            match event {
+                DebouncedEvent::Create(changed_path)
+                | DebouncedEvent::Write(changed_path)
+                | DebouncedEvent::Rename(_, changed_path) => {
+                    changed |= changed_path == path;
+                }
+
+                _ => {}
+            }
+        }
+
+        changed
+    }
+}
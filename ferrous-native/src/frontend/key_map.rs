@@ -0,0 +1,69 @@
+//! Contains the keyboard layouts the Chip-8 keypad can be mapped to.
+
+use glium::glutin::event::VirtualKeyCode;
+use serde::{Deserialize, Serialize};
+
+/// Keyboard layout of the Chip-8 keypad.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeyMap {
+    /// The hex keypad of the COSMAC VIP, on `1234`, `QWER`, `ASDF` and `ZXCV`.
+    Cosmac,
+
+    /// The COSMAC layout, with the arrow keys and `WASD` on keys 2, 4, 6 and 8,
+    /// and space on key 5.
+    Directions2468,
+
+    /// The COSMAC layout, with the arrow keys on keys 5, 7, 8 and 9 like `WASD`,
+    /// and space on key 6.
+    Directions5789,
+}
+
+impl KeyMap {
+    /// All the layouts, along with their names.
+    pub const ALL: [(Self, &'static str); 3] = [
+        (Self::Cosmac, "COSMAC VIP"),
+        (Self::Directions2468, "Arrows as 2/4/6/8"),
+        (Self::Directions5789, "Arrows as 5/7/8/9"),
+    ];
+
+    /// Get the keypad key the keyboard key is mapped to, if any.
+    pub fn key_index(self, keycode: VirtualKeyCode) -> Option<usize> {
+        use VirtualKeyCode::*;
+
+        let directional = match (self, keycode) {
+            (Self::Directions2468, Up | W) => Some(0x2),
+            (Self::Directions2468, Left | A) => Some(0x4),
+            (Self::Directions2468, Right | D) => Some(0x6),
+            (Self::Directions2468, Down | S) => Some(0x8),
+            (Self::Directions2468, Space) => Some(0x5),
+
+            (Self::Directions5789, Up) => Some(0x5),
+            (Self::Directions5789, Left) => Some(0x7),
+            (Self::Directions5789, Down) => Some(0x8),
+            (Self::Directions5789, Right) => Some(0x9),
+            (Self::Directions5789, Space) => Some(0x6),
+
+            _ => None,
+        };
+
+        directional.or(match keycode {
+            Key1 => Some(0x1),
+            Key2 => Some(0x2),
+            Key3 => Some(0x3),
+            Key4 => Some(0xC),
+            Q => Some(0x4),
+            W => Some(0x5),
+            E => Some(0x6),
+            R => Some(0xD),
+            A => Some(0x7),
+            S => Some(0x8),
+            D => Some(0x9),
+            F => Some(0xE),
+            Z => Some(0xA),
+            X => Some(0x0),
+            C => Some(0xB),
+            V => Some(0xF),
+            _ => None,
+        })
+    }
+}
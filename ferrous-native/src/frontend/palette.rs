@@ -0,0 +1,125 @@
+//! Contains named color palettes, which can be imported from
+//! and exported to JSON files, shared between ROMs and users.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::sidecar::{deserialize_color, parse_hex_color};
+
+/// File the recently used palettes are persisted to between sessions.
+const RECENT_PALETTES_FILE: &str = "ferrous_palettes.json";
+
+/// Number of recently used palettes that are remembered.
+const RECENT_PALETTES_LENGTH: usize = 8;
+
+/// A named pair of display colors.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Palette {
+    /// Name shown in the palette window.
+    pub name: String,
+
+    /// Foreground color.
+    #[serde(deserialize_with = "deserialize_color")]
+    pub fg_color: [f32; 3],
+
+    /// Background color.
+    #[serde(deserialize_with = "deserialize_color")]
+    pub bg_color: [f32; 3],
+}
+
+impl Palette {
+    /// Create a palette from colors of the form `#RRGGBB`.
+    ///
+    /// Panics if a color is invalid, this is meant for the built-in palettes.
+    fn from_hex(name: &str, fg_color: &str, bg_color: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            fg_color: parse_hex_color(fg_color).expect("Invalid built-in palette color."),
+            bg_color: parse_hex_color(bg_color).expect("Invalid built-in palette color."),
+        }
+    }
+
+    /// Get the palettes shipped with the emulator.
+    pub fn built_in() -> Vec<Self> {
+        vec![
+            Self::from_hex("Monochrome", "#FFFFFF", "#000000"),
+            Self::from_hex("Gameboy Green", "#0F380F", "#9BBC0F"),
+            Self::from_hex("Amber Monochrome", "#FFB000", "#1A1000"),
+            Self::from_hex("Octo Default", "#FFCC00", "#996600"),
+        ]
+    }
+
+    /// Load a palette from the given JSON file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let data = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+
+        serde_json::from_str(&data).map_err(|error| error.to_string())
+    }
+
+    /// Write the palette to the given JSON file.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let serialized = serde_json::to_string_pretty(self).expect("Failed to serialize palette.");
+
+        std::fs::write(path, serialized).map_err(|error| error.to_string())
+    }
+}
+
+/// Load the palettes used in previous sessions, most recent first.
+pub fn load_recent() -> Vec<Palette> {
+    let data = match std::fs::read_to_string(RECENT_PALETTES_FILE) {
+        Ok(data) => data,
+        Err(_) => return Vec::new(),
+    };
+
+    match serde_json::from_str(&data) {
+        Ok(palettes) => palettes,
+        Err(error) => {
+            warn!("failed to parse recent palettes: {}.", error);
+            Vec::new()
+        }
+    }
+}
+
+/// Move the palette to the front of the recently used palettes,
+/// replacing any palette with the same name, and persist them.
+pub fn push_recent(recent: &mut Vec<Palette>, palette: Palette) {
+    recent.retain(|other| other.name != palette.name);
+    recent.insert(0, palette);
+    recent.truncate(RECENT_PALETTES_LENGTH);
+
+    let serialized = serde_json::to_string(recent).expect("Failed to serialize recent palettes.");
+
+    if std::fs::write(RECENT_PALETTES_FILE, serialized).is_err() {
+        warn!("failed to save recent palettes.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Palette;
+
+    #[test]
+    fn palettes_round_trip_through_json() {
+        let palette = Palette::from_hex("Test", "#FF8000", "#000080");
+
+        let serialized = serde_json::to_string(&palette).unwrap();
+        let parsed: Palette = serde_json::from_str(&serialized).unwrap();
+
+        assert!(parsed == palette);
+    }
+
+    #[test]
+    fn palettes_accept_hex_colors() {
+        let json = r##"{"name": "Hex", "fg_color": "#FFFFFF", "bg_color": "#000000"}"##;
+        let palette: Palette = serde_json::from_str(json).unwrap();
+
+        assert_eq!(palette.fg_color, [1.0; 3]);
+        assert_eq!(palette.bg_color, [0.0; 3]);
+    }
+
+    #[test]
+    fn built_in_palettes_are_valid() {
+        assert!(!Palette::built_in().is_empty());
+    }
+}
@@ -0,0 +1,124 @@
+//! Contains shareable profiles, which bundle every emulator setting
+//! into a single JSON file, e.g. to share a setup for a tricky ROM.
+//!
+//! Not to be confused with `ferrous::QuirkProfile`, which only presets the quirks.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::key_map::KeyMap;
+use super::sidecar::RomConfig;
+
+/// Version of the profile format written by this build.
+///
+/// Fields missing from older profiles take their default value, and
+/// fields unknown to this build, from newer profiles, are ignored.
+pub const PROFILE_VERSION: u32 = 1;
+
+/// Every setting that makes up the emulator's configuration.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    /// Version of the format the profile was written in.
+    pub version: u32,
+
+    /// Quirks, speed and colors, the same settings as a ROM's sidecar file.
+    #[serde(flatten)]
+    pub rom_config: RomConfig,
+
+    /// Frames emulated without rendering, after each rendered frame.
+    pub frame_skip: u8,
+
+    /// Time for the beep to fade in, in milliseconds.
+    pub beep_attack: u16,
+
+    /// Time for the beep to fade out, in milliseconds.
+    pub beep_release: u16,
+
+    /// Stereo position of the beep, from -1 (left) to 1 (right).
+    pub beep_pan: f32,
+
+    /// Keyboard layout of the keypad.
+    pub key_map: KeyMap,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            version: PROFILE_VERSION,
+            rom_config: RomConfig::default(),
+            frame_skip: 0,
+            beep_attack: 5,
+            beep_release: 5,
+            beep_pan: 0.0,
+            key_map: KeyMap::Cosmac,
+        }
+    }
+}
+
+impl Profile {
+    /// Load a profile from the given JSON file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let data = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+        let profile: Self = serde_json::from_str(&data).map_err(|error| error.to_string())?;
+
+        if profile.version > PROFILE_VERSION {
+            warn!("profile is from a newer version, some settings may be ignored.");
+        }
+
+        Ok(profile)
+    }
+
+    /// Write the profile to the given JSON file.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let serialized = serde_json::to_string_pretty(self).expect("Failed to serialize profile.");
+
+        std::fs::write(path, serialized).map_err(|error| error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Profile, PROFILE_VERSION};
+
+    #[test]
+    fn profiles_round_trip_through_json() {
+        let mut profile = Profile::default();
+        profile.rom_config.shift_quirk = true;
+        profile.rom_config.fg_color = [1.0, 0.5, 0.0];
+        profile.beep_pan = -0.5;
+
+        let serialized = serde_json::to_string(&profile).unwrap();
+        let parsed: Profile = serde_json::from_str(&serialized).unwrap();
+
+        assert!(parsed == profile);
+    }
+
+    #[test]
+    fn every_quirk_round_trips_through_json() {
+        let mut profile = Profile::default();
+        profile.rom_config.wrap_x = false;
+        profile.rom_config.key_release_quirk = true;
+
+        let serialized = serde_json::to_string(&profile).unwrap();
+        let parsed: Profile = serde_json::from_str(&serialized).unwrap();
+
+        assert!(!parsed.rom_config.wrap_x);
+        assert!(parsed.rom_config.wrap_y);
+        assert!(parsed.rom_config.key_release_quirk);
+    }
+
+    #[test]
+    fn missing_and_unknown_fields_are_tolerated() {
+        let json = r##"{"shift_quirk": true, "fg_color": "#FF0000", "future_setting": 1}"##;
+        let profile: Profile = serde_json::from_str(json).unwrap();
+
+        assert_eq!(profile.version, PROFILE_VERSION);
+        assert!(profile.rom_config.shift_quirk);
+        assert_eq!(profile.rom_config.fg_color, [1.0, 0.0, 0.0]);
+        assert_eq!(profile.rom_config.cycles_per_frame, 10);
+        assert!(profile.rom_config.wrap_x && profile.rom_config.wrap_y);
+        assert_eq!(profile.beep_attack, 5);
+    }
+}
@@ -0,0 +1,160 @@
+//! Contains per-ROM configuration, stored in a
+//! `<rom>.ferrous.toml` sidecar file next to the ROM.
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Settings that are remembered for each ROM.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RomConfig {
+    /// How I is advanced after Fx55, Fx65.
+    pub load_store_mode: ferrous::LoadStoreMode,
+
+    /// If we should ignore Vy in shift opcodes.
+    pub shift_quirk: bool,
+
+    /// Factor in the highest nibble of address to select register for jump.
+    pub jump_quirk: bool,
+
+    /// If logic instructions reset VF.
+    pub logic_quirk: bool,
+
+    /// If sprites drawn past the right edge wrap around to the left.
+    pub wrap_x: bool,
+
+    /// If sprites drawn past the bottom edge wrap around to the top.
+    pub wrap_y: bool,
+
+    /// If Fx0A should ignore keys that were already held when it started waiting.
+    pub key_release_quirk: bool,
+
+    /// CPU cycles to execute per frame.
+    pub cycles_per_frame: u16,
+
+    /// Should the speed be set in instructions per second?
+    pub ips_mode: bool,
+
+    /// Instructions to execute per second, in IPS mode.
+    pub instructions_per_second: u32,
+
+    /// Foreground color.
+    #[serde(deserialize_with = "deserialize_color")]
+    pub fg_color: [f32; 3],
+
+    /// Background color.
+    #[serde(deserialize_with = "deserialize_color")]
+    pub bg_color: [f32; 3],
+}
+
+/// Parse a color of the form `#RRGGBB`, the `#` being optional,
+/// into its channels from 0.0 to 1.0.
+pub fn parse_hex_color(text: &str) -> Result<[f32; 3], &'static str> {
+    let hex = text.strip_prefix('#').unwrap_or(text);
+
+    if hex.len() != 6 || !hex.bytes().all(|digit| digit.is_ascii_hexdigit()) {
+        return Err("expected a color of the form `#RRGGBB`.");
+    }
+
+    let value = u32::from_str_radix(hex, 16).unwrap();
+
+    Ok([16, 8, 0].map(|shift| ((value >> shift) & 0xFF) as f32 / 255.0))
+}
+
+/// Deserialize a color given either as `#RRGGBB`,
+/// or as an array of channels from 0.0 to 1.0.
+pub fn deserialize_color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[f32; 3], D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Color {
+        Hex(String),
+        Channels([f32; 3]),
+    }
+
+    match Color::deserialize(deserializer)? {
+        Color::Hex(hex) => parse_hex_color(&hex).map_err(serde::de::Error::custom),
+        Color::Channels(channels) => Ok(channels),
+    }
+}
+
+impl Default for RomConfig {
+    fn default() -> Self {
+        Self {
+            load_store_mode: ferrous::LoadStoreMode::IncrementByXPlus1,
+            shift_quirk: false,
+            jump_quirk: false,
+            logic_quirk: false,
+            wrap_x: true,
+            wrap_y: true,
+            key_release_quirk: false,
+            cycles_per_frame: 10,
+            ips_mode: false,
+            instructions_per_second: 600,
+            fg_color: [1.0; 3],
+            bg_color: [0.0; 3],
+        }
+    }
+}
+
+impl RomConfig {
+    /// Get the path of the sidecar file for the given ROM.
+    pub fn path_for(rom: &Path) -> PathBuf {
+        let mut path = OsString::from(rom);
+        path.push(".ferrous.toml");
+
+        PathBuf::from(path)
+    }
+
+    /// Load the sidecar file of the given ROM.
+    ///
+    /// Returns `None` if it doesn't exist, or can't be parsed.
+    pub fn load(rom: &Path) -> Option<Self> {
+        let data = std::fs::read_to_string(Self::path_for(rom)).ok()?;
+
+        match toml::from_str(&data) {
+            Ok(config) => Some(config),
+            Err(error) => {
+                warn!("failed to parse ROM configuration: {}.", error);
+                None
+            }
+        }
+    }
+
+    /// Write the sidecar file of the given ROM.
+    pub fn save(&self, rom: &Path) {
+        let serialized = toml::to_string(self).expect("Failed to serialize ROM configuration.");
+
+        if std::fs::write(Self::path_for(rom), serialized).is_err() {
+            warn!("failed to save ROM configuration.");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_hex_color, RomConfig};
+
+    #[test]
+    fn hex_colors_are_normalized() {
+        assert_eq!(parse_hex_color("#FF0000"), Ok([1.0, 0.0, 0.0]));
+        assert_eq!(parse_hex_color("00ff33"), Ok([0.0, 1.0, 0.2]));
+    }
+
+    #[test]
+    fn malformed_hex_colors_are_rejected() {
+        for text in ["", "#FFF", "#FF00000", "#GG0000", "#+FFFFF", "#FF 000"] {
+            assert!(parse_hex_color(text).is_err(), "{:?} was accepted", text);
+        }
+    }
+
+    #[test]
+    fn sidecar_colors_can_be_hex_or_channels() {
+        let config: RomConfig =
+            toml::from_str("fg_color = \"#336699\"\nbg_color = [0.5, 0.5, 0.5]").unwrap();
+
+        assert_eq!(config.fg_color, [0.2, 0.4, 0.6]);
+        assert_eq!(config.bg_color, [0.5; 3]);
+    }
+}
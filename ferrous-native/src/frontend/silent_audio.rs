@@ -0,0 +1,31 @@
+//! Stand-in for the audio subsystem, used when built without the `audio` feature.
+
+use std::time::Duration;
+
+/// Audio subsystem that never plays anything.
+pub struct Audio;
+
+impl Audio {
+    /// Create a new `Audio` instance.
+    pub fn new() -> Self {
+        Audio
+    }
+
+    /// Does nothing, there is no audio backend.
+    pub fn play_beep(&self) {}
+
+    /// Does nothing, there is no audio backend.
+    pub fn pause_beep(&self) {}
+
+    /// Does nothing, there is no audio backend.
+    pub fn set_paused(&self, _paused: bool) {}
+
+    /// Does nothing, there is no audio backend.
+    pub fn cut_beep(&self) {}
+
+    /// Does nothing, there is no audio backend.
+    pub fn set_envelope(&self, _attack: Duration, _release: Duration) {}
+
+    /// Does nothing, there is no audio backend.
+    pub fn set_pan(&self, _pan: f32) {}
+}
@@ -0,0 +1,184 @@
+//! Contains the parser and evaluator for debugger watch expressions.
+//!
+//! An expression is a sum of terms, separated by `+` or `-`. A term is a
+//! number (decimal, or hexadecimal with a `0x` prefix), a register (`V0` to
+//! `VF`, `I`, `PC`, `SP`, `DT` or `ST`), a parenthesized expression, or an
+//! expression in brackets, which reads the byte at that address.
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// An expression added to the watch window.
+pub struct Watch {
+    /// Expression as it was typed.
+    pub source: String,
+
+    /// Parsed expression, or why it couldn't be parsed.
+    pub expr: Result<Expr, &'static str>,
+}
+
+impl Watch {
+    /// Create a new watch, parsing the given expression.
+    pub fn new(source: &str) -> Self {
+        Self {
+            source: source.trim().to_string(),
+            expr: Expr::parse(source),
+        }
+    }
+
+    /// Format the current value of the watch, or the reason it has none.
+    pub fn value(&self, cpu: &ferrous::Ferrous) -> String {
+        match &self.expr {
+            Ok(expr) => match expr.evaluate(cpu) {
+                Some(value) => format!("{:#06X} ({})", value, value),
+                None => "address out of bounds".to_string(),
+            },
+
+            Err(error) => error.to_string(),
+        }
+    }
+}
+
+/// A parsed watch expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(usize),
+    Register(usize),
+    Index,
+    ProgramCounter,
+    StackPointer,
+    DelayTimer,
+    SoundTimer,
+    Memory(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// Parse the given watch expression.
+    pub fn parse(source: &str) -> Result<Self, &'static str> {
+        let mut chars = source.chars().peekable();
+        let expr = parse_sum(&mut chars)?;
+
+        skip_whitespace(&mut chars);
+
+        match chars.next() {
+            None => Ok(expr),
+            Some(_) => Err("unexpected trailing input"),
+        }
+    }
+
+    /// Evaluate the expression against the current interpreter state.
+    ///
+    /// Returns `None`, if it reads memory out of bounds.
+    pub fn evaluate(&self, cpu: &ferrous::Ferrous) -> Option<usize> {
+        let value = match self {
+            Self::Number(value) => *value,
+            Self::Register(index) => cpu.reg[*index] as usize,
+            Self::Index => cpu.id,
+            Self::ProgramCounter => cpu.pc,
+            Self::StackPointer => cpu.sp,
+            Self::DelayTimer => cpu.dt as usize,
+            Self::SoundTimer => cpu.st as usize,
+            Self::Memory(address) => *cpu.ram.get(address.evaluate(cpu)?)? as usize,
+            Self::Add(lhs, rhs) => lhs.evaluate(cpu)?.wrapping_add(rhs.evaluate(cpu)?),
+            Self::Sub(lhs, rhs) => lhs.evaluate(cpu)?.wrapping_sub(rhs.evaluate(cpu)?),
+        };
+
+        Some(value)
+    }
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while matches!(chars.peek(), Some(c) if c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+/// sum := term (('+' | '-') term)*
+fn parse_sum(chars: &mut Peekable<Chars>) -> Result<Expr, &'static str> {
+    let mut expr = parse_term(chars)?;
+
+    loop {
+        skip_whitespace(chars);
+
+        match chars.peek() {
+            Some('+') => {
+                chars.next();
+                expr = Expr::Add(Box::new(expr), Box::new(parse_term(chars)?));
+            }
+
+            Some('-') => {
+                chars.next();
+                expr = Expr::Sub(Box::new(expr), Box::new(parse_term(chars)?));
+            }
+
+            _ => return Ok(expr),
+        }
+    }
+}
+
+/// term := number | register | '(' sum ')' | '[' sum ']'
+fn parse_term(chars: &mut Peekable<Chars>) -> Result<Expr, &'static str> {
+    skip_whitespace(chars);
+
+    match chars.peek() {
+        Some('(') | Some('[') => {
+            let is_memory = chars.next() == Some('[');
+            let expr = parse_sum(chars)?;
+
+            skip_whitespace(chars);
+
+            match (chars.next(), is_memory) {
+                (Some(')'), false) => Ok(expr),
+                (Some(']'), true) => Ok(Expr::Memory(Box::new(expr))),
+                _ => Err("unbalanced brackets"),
+            }
+        }
+
+        Some(c) if c.is_ascii_alphanumeric() => {
+            let mut word = String::new();
+
+            while let Some(&c) = chars.peek() {
+                if !c.is_ascii_alphanumeric() {
+                    break;
+                }
+
+                word.push(c.to_ascii_uppercase());
+                chars.next();
+            }
+
+            parse_word(&word)
+        }
+
+        Some(_) => Err("unexpected character"),
+        None => Err("unexpected end of expression"),
+    }
+}
+
+/// Parse a number or register name.
+fn parse_word(word: &str) -> Result<Expr, &'static str> {
+    let expr = match word {
+        "I" => Expr::Index,
+        "PC" => Expr::ProgramCounter,
+        "SP" => Expr::StackPointer,
+        "DT" => Expr::DelayTimer,
+        "ST" => Expr::SoundTimer,
+
+        _ if word.len() == 2 && word.starts_with('V') => {
+            let index = usize::from_str_radix(&word[1..], 16).map_err(|_| "unknown register")?;
+
+            Expr::Register(index)
+        }
+
+        _ if word.starts_with("0X") => {
+            let value = usize::from_str_radix(&word[2..], 16).map_err(|_| "invalid hex number")?;
+
+            Expr::Number(value)
+        }
+
+        _ => Expr::Number(word.parse().map_err(|_| "unknown name or invalid number")?),
+    };
+
+    Ok(expr)
+}
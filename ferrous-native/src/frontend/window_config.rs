@@ -0,0 +1,115 @@
+//! Contains the window geometry and the V-Sync option,
+//! which are remembered between sessions.
+
+use glium::glutin::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event_loop::EventLoop,
+    monitor::MonitorHandle,
+    window::Window,
+};
+use serde::{Deserialize, Serialize};
+
+/// File the window configuration is persisted to between sessions.
+const WINDOW_CONFIG_FILE: &str = "ferrous_window.toml";
+
+/// Size and position of the window, in physical pixels,
+/// and whether its frames are paced by V-Sync.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowConfig {
+    /// Size of the window's client area.
+    pub size: [u32; 2],
+
+    /// Position of the window's top left corner, including decorations.
+    pub position: [i32; 2],
+
+    /// Is the GL context created with V-Sync?
+    #[serde(default)]
+    pub vsync: bool,
+}
+
+impl WindowConfig {
+    /// Load the window configuration of the last session.
+    ///
+    /// Returns `None` if it doesn't exist, or can't be parsed.
+    pub fn load() -> Option<Self> {
+        let data = std::fs::read_to_string(WINDOW_CONFIG_FILE).ok()?;
+
+        match toml::from_str(&data) {
+            Ok(config) => Some(config),
+            Err(error) => {
+                warn!("failed to parse window configuration: {}.", error);
+                None
+            }
+        }
+    }
+
+    /// Capture the geometry of the given window, along with the V-Sync option.
+    ///
+    /// Returns `None` if it is fullscreen, or its position is unknown.
+    pub fn capture(window: &Window, vsync: bool) -> Option<Self> {
+        if window.fullscreen().is_some() {
+            return None;
+        }
+
+        let size = window.inner_size();
+        let position = window.outer_position().ok()?;
+
+        Some(Self {
+            size: [size.width, size.height],
+            position: [position.x, position.y],
+            vsync,
+        })
+    }
+
+    /// Write the window configuration for the next session.
+    pub fn save(&self) {
+        let serialized = toml::to_string(self).expect("Failed to serialize window configuration.");
+
+        if std::fs::write(WINDOW_CONFIG_FILE, serialized).is_err() {
+            warn!("failed to save window configuration.");
+        }
+    }
+
+    /// Move the window onto a connected monitor, in case the monitor
+    /// it was on last session has been disconnected or rearranged.
+    pub fn clamp_to_monitors(mut self, event_loop: &EventLoop<()>) -> Self {
+        let [x, y] = self.position;
+
+        let monitors = event_loop.available_monitors().collect::<Vec<_>>();
+
+        // Keep the window on the monitor its corner is on, or else
+        // bring it back to the primary monitor.
+        let monitor = match monitors.iter().find(|m| contains(m, x, y)) {
+            Some(monitor) => monitor.clone(),
+            None => match event_loop
+                .primary_monitor()
+                .or_else(|| monitors.first().cloned())
+            {
+                Some(monitor) => monitor,
+                None => return self,
+            },
+        };
+
+        let PhysicalPosition { x: left, y: top } = monitor.position();
+        let PhysicalSize { width, height } = monitor.size();
+
+        // Fit the window inside the monitor, and then move it so all of it is visible.
+        self.size[0] = self.size[0].min(width);
+        self.size[1] = self.size[1].min(height);
+
+        self.position = [
+            x.clamp(left, left + (width - self.size[0]) as i32),
+            y.clamp(top, top + (height - self.size[1]) as i32),
+        ];
+
+        self
+    }
+}
+
+/// Is the given point on the monitor?
+fn contains(monitor: &MonitorHandle, x: i32, y: i32) -> bool {
+    let PhysicalPosition { x: left, y: top } = monitor.position();
+    let PhysicalSize { width, height } = monitor.size();
+
+    (left..left + width as i32).contains(&x) && (top..top + height as i32).contains(&y)
+}
@@ -0,0 +1,224 @@
+//! Contains the headless runner, used for scripting and CI.
+
+use std::path::Path;
+
+use ferrous::CpuEvent;
+
+use crate::frontend::{self, Options};
+use crate::input_script::InputScript;
+
+/// Instructions printed before a divergence from a reference trace.
+const TRACE_CONTEXT: usize = 8;
+
+/// Result of a headless run.
+pub struct Outcome {
+    /// Sound events emitted during the run, with the frame they were emitted on.
+    pub sound_events: Vec<(u32, CpuEvent)>,
+}
+
+/// Run the ROM for the requested number of frames without
+/// opening a window, and optionally dump the final state.
+pub fn run(options: Options) -> Outcome {
+    let path = options.rom.as_ref().expect("No ROM was provided.");
+    let mut cpu = ferrous::Ferrous::new();
+
+    frontend::apply_quirks(&mut cpu, &options);
+
+    let cycles = options.cycles.unwrap_or_else(|| cpu.cycles_per_tick());
+    load_rom_or_exit(&mut cpu, path);
+
+    let outcome = run_frames(
+        &mut cpu,
+        options.frames,
+        cycles,
+        options.input_script.as_ref(),
+    );
+
+    if options.dump_vram {
+        dump_vram(&cpu);
+        dump_registers(&cpu);
+    }
+
+    if options.dump_sound {
+        dump_sound(&outcome);
+    }
+
+    outcome
+}
+
+/// Run the ROM against a reference trace, printing the first divergence
+/// along with the instructions leading up to it.
+///
+/// Returns whether the whole trace matched.
+pub fn compare_trace(options: &Options, path: &Path) -> bool {
+    let rom = options.rom.as_ref().expect("No ROM was provided.");
+
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) => {
+            eprintln!("Failed to read trace, {}", error);
+            return false;
+        }
+    };
+
+    let trace = match ferrous::parse_trace(&text) {
+        Ok(trace) => trace,
+        Err(error) => {
+            eprintln!("Failed to parse trace, {}", error);
+            return false;
+        }
+    };
+
+    let mut cpu = ferrous::Ferrous::new();
+
+    frontend::apply_quirks(&mut cpu, options);
+    load_rom_or_exit(&mut cpu, rom);
+
+    let cycles = options.cycles.unwrap_or_else(|| cpu.cycles_per_tick());
+
+    match cpu.compare_trace(&trace, cycles) {
+        Ok(()) => {
+            println!("Matched all {} instructions of the trace.", trace.len());
+            true
+        }
+
+        Err(divergence) => {
+            for entry in &trace[divergence.cycle.saturating_sub(TRACE_CONTEXT)..divergence.cycle] {
+                println!("    {}", entry);
+            }
+
+            println!("Diverged at {}.", divergence);
+            false
+        }
+    }
+}
+
+/// Load the ROM at the given path, exiting with an error
+/// if it can't be read or doesn't fit in memory.
+fn load_rom_or_exit(cpu: &mut ferrous::Ferrous, path: &Path) {
+    let loaded = crate::rom_file::read(path)
+        .map_err(|error| error.to_string())
+        .and_then(|data| cpu.load_rom(&data).map_err(String::from));
+
+    if let Err(error) = loaded {
+        warn!("failed to load {}: {}", path.display(), error);
+        std::process::exit(1);
+    }
+}
+
+/// Run the interpreter for the given number of frames,
+/// collecting the sound events emitted on each.
+fn run_frames(
+    cpu: &mut ferrous::Ferrous,
+    frames: u32,
+    cycles: u16,
+    input_script: Option<&InputScript>,
+) -> Outcome {
+    let mut sound_events = Vec::new();
+
+    for frame in 0..frames {
+        if let Some(script) = input_script {
+            script.apply(cpu, frame);
+        }
+
+        for _ in 0..cycles {
+            if cpu.is_halted {
+                break;
+            }
+
+            let pc = cpu.pc;
+
+            if cpu.execute_cycle().is_none() {
+                frontend::log_failed_cycle(cpu, pc);
+            }
+        }
+
+        cpu.step_timers();
+
+        // Drained every frame, so that none of them are dropped.
+        for event in cpu.drain_events() {
+            if let CpuEvent::SoundOn | CpuEvent::SoundOff = event {
+                sound_events.push((frame, event));
+            }
+        }
+    }
+
+    Outcome { sound_events }
+}
+
+/// Print the sound events of a run, one per line.
+fn dump_sound(outcome: &Outcome) {
+    for (frame, event) in &outcome.sound_events {
+        let state = match event {
+            CpuEvent::SoundOn => "on",
+            _ => "off",
+        };
+
+        println!("Frame {}: sound {}", frame, state);
+    }
+}
+
+/// Print the active screen as ASCII art.
+fn dump_vram(cpu: &ferrous::Ferrous) {
+    for row in cpu.vram_rows() {
+        let line: String = row
+            .iter()
+            .map(|&pixel| if pixel == 0 { '.' } else { '#' })
+            .collect();
+
+        println!("{}", line);
+    }
+}
+
+/// Print the register state of the CPU.
+fn dump_registers(cpu: &ferrous::Ferrous) {
+    println!(
+        "PC: {:#06X}  I: {:#06X}  SP: {:#04X}  DT: {:#04X}  ST: {:#04X}",
+        cpu.pc, cpu.id, cpu.sp, cpu.dt, cpu.st
+    );
+
+    for (i, v) in cpu.reg.iter().enumerate() {
+        print!("V{:X}: {:#04X}", i, v);
+
+        if i % 8 == 7 {
+            println!();
+        } else {
+            print!("  ");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::run_frames;
+    use ferrous::CpuEvent;
+
+    #[test]
+    fn beep_is_recorded_with_its_frames() {
+        let mut cpu = ferrous::Ferrous::new();
+
+        // LD V0, 0x10; LD ST, V0; LD V0, 0x00; LD ST, V0; JP 0x208
+        cpu.load_rom(&[0x60, 0x10, 0xF0, 0x18, 0x60, 0x00, 0xF0, 0x18, 0x12, 0x08])
+            .unwrap();
+
+        let outcome = run_frames(&mut cpu, 6, 1, None);
+
+        // The beep lasts until the end of the tick in which it was cleared.
+        assert_eq!(
+            outcome.sound_events,
+            vec![(1, CpuEvent::SoundOn), (3, CpuEvent::SoundOff)]
+        );
+    }
+
+    #[test]
+    fn silent_rom_records_no_sound() {
+        let mut cpu = ferrous::Ferrous::new();
+
+        // LD V0, 0x10; JP 0x202
+        cpu.load_rom(&[0x60, 0x10, 0x12, 0x02]).unwrap();
+
+        let outcome = run_frames(&mut cpu, 10, 10, None);
+
+        assert!(outcome.sound_events.is_empty());
+    }
+}
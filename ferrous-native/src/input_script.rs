@@ -0,0 +1,97 @@
+//! Contains the parser for input scripts, which press keys
+//! on given frames for reproducible demos and tests.
+//!
+//! Each line of a script has the form `frame: keys`, where keys is a
+//! whitespace separated list of hex digits, e.g. `10: 1 2 F`. The keys
+//! are pressed on that frame, and released on the next one. Empty lines
+//! and lines starting with `#` are ignored.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Key presses read from an input script.
+pub struct InputScript {
+    /// Keys pressed on each frame, one bit per key.
+    frames: BTreeMap<u32, u16>,
+}
+
+impl InputScript {
+    /// Read and parse the input script at the given path.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|error| format!("failed to read input script: {}.", error))?;
+
+        Self::parse(&text)
+    }
+
+    /// Parse the given input script.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut frames = BTreeMap::new();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (frame, keys) = match line.split_once(':') {
+                Some(parts) => parts,
+                None => {
+                    return Err(format!(
+                        "line {}: expected `frame: keys`, found `{}`.",
+                        number + 1,
+                        line
+                    ))
+                }
+            };
+
+            let frame = frame.trim().parse::<u32>().map_err(|_| {
+                format!(
+                    "line {}: expected a frame number, found `{}`.",
+                    number + 1,
+                    frame.trim()
+                )
+            })?;
+
+            let mut mask = 0;
+
+            for key in keys.split_whitespace() {
+                match u8::from_str_radix(key, 16) {
+                    Ok(index) if key.len() == 1 => mask |= 1 << index,
+                    _ => {
+                        return Err(format!(
+                            "line {}: expected a key between 0 and F, found `{}`.",
+                            number + 1,
+                            key
+                        ))
+                    }
+                }
+            }
+
+            *frames.entry(frame).or_insert(0) |= mask;
+        }
+
+        Ok(Self { frames })
+    }
+
+    /// Press the keys listed for the given frame,
+    /// and release the ones pressed on the previous frame.
+    pub fn apply(&self, cpu: &mut ferrous::Ferrous, frame: u32) {
+        let pressed = self.frames.get(&frame).copied().unwrap_or(0);
+        let released = frame
+            .checked_sub(1)
+            .and_then(|previous| self.frames.get(&previous))
+            .copied()
+            .unwrap_or(0);
+
+        for index in 0..16 {
+            if pressed & (1 << index) != 0 {
+                cpu.queue_key_press(index as u8);
+                cpu.set_key_at_index(index, true);
+            } else if released & (1 << index) != 0 {
+                cpu.set_key_at_index(index, false);
+            }
+        }
+    }
+}
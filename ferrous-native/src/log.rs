@@ -0,0 +1,48 @@
+//! Contains the frontend's log, printed to stderr and, when `--log` is
+//! passed, also written to a file, e.g. to attach to a bug report.
+
+use std::fmt;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// File the log is written to, if any.
+static LOG_FILE: Mutex<Option<File>> = Mutex::new(None);
+
+/// Log a warning, e.g. an invalid opcode or a failed save.
+macro_rules! warn {
+    ($($arg:tt)*) => {
+        $crate::log::write("WARN", format_args!($($arg)*))
+    };
+}
+
+/// Log an event, e.g. a ROM being loaded or a resolution switch.
+macro_rules! info {
+    ($($arg:tt)*) => {
+        $crate::log::write("INFO", format_args!($($arg)*))
+    };
+}
+
+/// Write the log to the given file from now on, as well as to stderr.
+pub fn open(path: &Path) -> std::io::Result<()> {
+    let file = File::create(path)?;
+
+    if let Ok(mut log_file) = LOG_FILE.lock() {
+        *log_file = Some(file);
+    }
+
+    Ok(())
+}
+
+/// Print the message along with its level, and append it to the log file, if any.
+pub fn write(level: &str, message: fmt::Arguments) {
+    eprintln!("[{}] {}", level, message);
+
+    if let Ok(mut log_file) = LOG_FILE.lock() {
+        if let Some(file) = log_file.as_mut() {
+            // Failing to log isn't worth interrupting emulation for.
+            let _ = writeln!(file, "[{}] {}", level, message);
+        }
+    }
+}
@@ -1,5 +1,242 @@
+use std::path::{Path, PathBuf};
+
+use clap::{App, Arg};
+
+#[macro_use]
+mod log;
+
 mod frontend;
+mod headless;
+mod input_script;
+mod rom_file;
+
+use input_script::InputScript;
+
+/// Validate that the cycles argument is a positive integer,
+/// that fits in the frontend's cycles per frame counter.
+fn validate_cycles(value: String) -> Result<(), String> {
+    match value.parse::<u16>() {
+        Ok(cycles) if cycles > 0 => Ok(()),
+        _ => Err(format!(
+            "expected an integer between 1 and {}, found `{}`.",
+            u16::MAX,
+            value
+        )),
+    }
+}
+
+/// Validate that the scale argument is a positive integer.
+fn validate_scale(value: String) -> Result<(), String> {
+    match value.parse::<u32>() {
+        Ok(scale) if scale > 0 => Ok(()),
+        _ => Err(format!("expected a positive integer, found `{}`.", value)),
+    }
+}
+
+/// Validate that the frames argument is a non-negative integer.
+fn validate_frames(value: String) -> Result<(), String> {
+    match value.parse::<u32>() {
+        Ok(_) => Ok(()),
+        _ => Err(format!(
+            "expected a non-negative integer, found `{}`.",
+            value
+        )),
+    }
+}
+
+/// Validate that the color argument is of the form `#RRGGBB`.
+fn validate_color(value: String) -> Result<(), String> {
+    frontend::parse_hex_color(&value)
+        .map(|_| ())
+        .map_err(String::from)
+}
+
+/// Validate that the input script argument is a readable, well formed script.
+fn validate_input_script(value: String) -> Result<(), String> {
+    InputScript::load(Path::new(&value)).map(|_| ())
+}
 
 fn main() {
-    frontend::start();
+    let matches = App::new("Ferrous")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("A (super) Chip-8 interpreter written in Rust.")
+        .arg(
+            Arg::with_name("rom")
+                .value_name("ROM")
+                .help("Path to a ROM that is loaded and started immediately.")
+                .index(1),
+        )
+        .arg(
+            Arg::with_name("cycles")
+                .short("c")
+                .long("cycles")
+                .value_name("CYCLES")
+                .takes_value(true)
+                .validator(validate_cycles)
+                .help("Number of CPU cycles to execute per frame, between 1 and 65535."),
+        )
+        .arg(
+            Arg::with_name("max-catchup")
+                .long("max-catchup")
+                .value_name("FRAMES")
+                .takes_value(true)
+                .validator(validate_frames)
+                .help("Number of frames to catch up on after the emulator stalls, the rest are dropped."),
+        )
+        .arg(
+            Arg::with_name("fullscreen")
+                .short("f")
+                .long("fullscreen")
+                .help("Start the emulator in borderless fullscreen mode."),
+        )
+        .arg(
+            Arg::with_name("scale")
+                .long("scale")
+                .value_name("SCALE")
+                .takes_value(true)
+                .validator(validate_scale)
+                .help("Integer scale of the initial window size, relative to 128x64."),
+        )
+        .arg(
+            Arg::with_name("fg-color")
+                .long("fg-color")
+                .value_name("COLOR")
+                .takes_value(true)
+                .validator(validate_color)
+                .help("Foreground color, as `#RRGGBB`."),
+        )
+        .arg(
+            Arg::with_name("bg-color")
+                .long("bg-color")
+                .value_name("COLOR")
+                .takes_value(true)
+                .validator(validate_color)
+                .help("Background color, as `#RRGGBB`."),
+        )
+        .arg(
+            Arg::with_name("quirks")
+                .short("q")
+                .long("quirks")
+                .value_name("PROFILE")
+                .takes_value(true)
+                .possible_values(&["cosmac", "schip-modern", "schip-legacy", "xochip"])
+                .help("Quirk profile matching the behaviour and speed of a platform."),
+        )
+        .arg(
+            Arg::with_name("load-store")
+                .short("l")
+                .long("load-store")
+                .help("Enable the load/store quirk, overriding the quirk profile."),
+        )
+        .arg(
+            Arg::with_name("shift")
+                .short("s")
+                .long("shift")
+                .help("Enable the shift quirk, overriding the quirk profile."),
+        )
+        .arg(
+            Arg::with_name("jump")
+                .short("j")
+                .long("jump")
+                .help("Enable the jump quirk, overriding the quirk profile."),
+        )
+        .arg(
+            Arg::with_name("logic")
+                .long("logic")
+                .help("Enable the logic quirk, overriding the quirk profile."),
+        )
+        .arg(
+            Arg::with_name("headless")
+                .long("headless")
+                .requires("rom")
+                .help("Run the ROM without opening a window."),
+        )
+        .arg(
+            Arg::with_name("frames")
+                .long("frames")
+                .value_name("FRAMES")
+                .takes_value(true)
+                .default_value("60")
+                .validator(validate_frames)
+                .help("Number of frames to run in headless mode."),
+        )
+        .arg(
+            Arg::with_name("input-script")
+                .long("input-script")
+                .value_name("FILE")
+                .takes_value(true)
+                .requires("rom")
+                .validator(validate_input_script)
+                .help("Press keys on given frames, read from `frame: keys` lines."),
+        )
+        .arg(
+            Arg::with_name("compare-trace")
+                .long("compare-trace")
+                .value_name("FILE")
+                .takes_value(true)
+                .requires("rom")
+                .help("Check the ROM's execution against a trace of `pc opcode` lines, then exit."),
+        )
+        .arg(
+            Arg::with_name("dump-vram")
+                .long("dump-vram")
+                .requires("headless")
+                .help("Print the screen and registers after a headless run."),
+        )
+        .arg(
+            Arg::with_name("dump-sound")
+                .long("dump-sound")
+                .requires("headless")
+                .help("Print the frames the beep started and stopped on after a headless run."),
+        )
+        .arg(
+            Arg::with_name("log")
+                .long("log")
+                .value_name("FILE")
+                .takes_value(true)
+                .help("Also write warnings and events to the file, e.g. for a bug report."),
+        )
+        .get_matches();
+
+    if let Some(path) = matches.value_of_os("log") {
+        if let Err(error) = log::open(Path::new(path)) {
+            warn!("failed to open the log file: {}.", error);
+        }
+    }
+
+    let options = frontend::Options {
+        rom: matches.value_of_os("rom").map(PathBuf::from),
+        cycles: matches.value_of("cycles").and_then(|c| c.parse().ok()),
+        max_catchup_frames: matches.value_of("max-catchup").and_then(|f| f.parse().ok()),
+        fullscreen: matches.is_present("fullscreen"),
+        scale: matches.value_of("scale").and_then(|s| s.parse().ok()),
+        quirks: matches.value_of("quirks").and_then(|q| q.parse().ok()),
+        load_store_quirk: matches.is_present("load-store"),
+        shift_quirk: matches.is_present("shift"),
+        jump_quirk: matches.is_present("jump"),
+        logic_quirk: matches.is_present("logic"),
+        fg_color: matches.value_of("fg-color").map(String::from),
+        bg_color: matches.value_of("bg-color").map(String::from),
+        headless: matches.is_present("headless"),
+        frames: matches
+            .value_of("frames")
+            .and_then(|f| f.parse().ok())
+            .unwrap_or(60),
+        dump_vram: matches.is_present("dump-vram"),
+        dump_sound: matches.is_present("dump-sound"),
+        reference_trace: matches.value_of_os("compare-trace").map(PathBuf::from),
+        input_script: matches
+            .value_of_os("input-script")
+            .and_then(|path| InputScript::load(Path::new(path)).ok()),
+    };
+
+    if let Some(trace) = &options.reference_trace {
+        if !headless::compare_trace(&options, trace) {
+            std::process::exit(1);
+        }
+    } else if options.headless {
+        headless::run(options);
+    } else {
+        frontend::start(options);
+    }
 }
@@ -0,0 +1,89 @@
+//! Contains the reader for ROM files, which may be gzip-compressed.
+
+use std::io;
+use std::path::Path;
+
+/// Magic bytes at the start of every gzip stream.
+#[cfg(feature = "gzip")]
+const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
+
+/// Upper bound on the decompressed size of a ROM.
+///
+/// Anything larger than memory is rejected by the interpreter anyway,
+/// this just stops a malicious archive from filling up RAM.
+#[cfg(feature = "gzip")]
+const DECOMPRESSED_LIMIT: u64 = 0x1000;
+
+/// Read the ROM at the given path, decompressing it if it is gzipped.
+pub fn read(path: &Path) -> io::Result<Vec<u8>> {
+    decompress(std::fs::read(path)?)
+}
+
+/// Decompress the given data if it is gzipped, otherwise return it unchanged.
+#[cfg(feature = "gzip")]
+pub fn decompress(data: Vec<u8>) -> io::Result<Vec<u8>> {
+    use std::io::Read;
+
+    if !data.starts_with(&GZIP_MAGIC) {
+        return Ok(data);
+    }
+
+    let mut rom = Vec::new();
+
+    flate2::read::GzDecoder::new(data.as_slice())
+        .take(DECOMPRESSED_LIMIT + 1)
+        .read_to_end(&mut rom)?;
+
+    Ok(rom)
+}
+
+/// Gzip support is disabled, so the data is always returned unchanged.
+#[cfg(not(feature = "gzip"))]
+pub fn decompress(data: Vec<u8>) -> io::Result<Vec<u8>> {
+    Ok(data)
+}
+
+#[cfg(all(test, feature = "gzip"))]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{write::GzEncoder, Compression};
+
+    use super::decompress;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn gzipped_rom_is_decompressed_and_loaded() {
+        // 0x200: LD V0, 0x2A
+        let rom = [0x60, 0x2A];
+        let data = decompress(gzip(&rom)).unwrap();
+
+        let mut cpu = ferrous::Ferrous::new();
+        cpu.load_rom(&data).unwrap();
+        cpu.execute_cycle();
+
+        assert_eq!(data, rom);
+        assert_eq!(cpu.reg[0], 0x2A);
+    }
+
+    #[test]
+    fn plain_rom_is_unchanged() {
+        let rom = vec![0x60, 0x2A];
+
+        assert_eq!(decompress(rom.clone()).unwrap(), rom);
+    }
+
+    #[test]
+    fn size_limit_applies_after_decompression() {
+        // Compresses far below the limit, but is too large once decompressed.
+        let data = decompress(gzip(&[0; 0x2000])).unwrap();
+
+        assert!(ferrous::Ferrous::new().load_rom(&data).is_err());
+    }
+}
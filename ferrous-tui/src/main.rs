@@ -0,0 +1,213 @@
+//! A terminal frontend for Ferrous, built on crossterm, for playing
+//! ROMs over SSH or anywhere else without a GUI stack.
+//! Like the minifb frontend, it has no debugger or menus.
+//!
+//! Each line of text shows two rows of pixels, using half block characters.
+
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::terminal::{self, ClearType};
+use crossterm::{cursor, execute, queue, style::Print};
+
+/// CPU cycles to execute per frame.
+const CYCLES_PER_FRAME: u16 = 10;
+
+/// Time between frames, roughly 60 frames per second.
+const FRAME_TIME: Duration = Duration::from_micros(16_667);
+
+/// Frames a key stays held after it was pressed.
+///
+/// Terminals only report key presses, and repeat them while a key is
+/// held, so a key is released once it hasn't been repeated for a while.
+const KEY_HOLD_FRAMES: u8 = 8;
+
+/// Keyboard keys, in the order of the Chip-8 keys they map to.
+const KEYMAP: [char; 16] = [
+    'x', '1', '2', '3', 'q', 'w', 'e', 'a', 's', 'd', 'z', 'c', '4', 'r', 'f', 'v',
+];
+
+/// Raw mode and the alternate screen, which are
+/// left when dropped, even if the frontend panics.
+struct RawTerminal;
+
+impl RawTerminal {
+    fn enter() -> crossterm::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+
+        Ok(Self)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Get the character showing the given top and bottom pixels.
+fn half_block(top: bool, bottom: bool) -> char {
+    match (top, bottom) {
+        (false, false) => ' ',
+        (true, false) => '▀',
+        (false, true) => '▄',
+        (true, true) => '█',
+    }
+}
+
+/// Convert the interpreter's screen to lines of half block characters.
+fn render_lines(cpu: &ferrous::Ferrous) -> Vec<String> {
+    let rows: Vec<&[u8]> = cpu.vram_rows().collect();
+
+    rows.chunks_exact(2)
+        .map(|pair| {
+            pair[0]
+                .iter()
+                .zip(pair[1])
+                .map(|(&top, &bottom)| half_block(top != 0, bottom != 0))
+                .collect()
+        })
+        .collect()
+}
+
+/// Draw the lines centered in the terminal, or a notice
+/// if the terminal is too small to fit them.
+///
+/// The terminal only needs clearing after it was resized,
+/// otherwise the lines overwrite the previous ones.
+fn draw(out: &mut impl Write, lines: &[String], clear: bool) -> crossterm::Result<()> {
+    let (columns, rows) = terminal::size()?;
+    let width = lines.first().map_or(0, |line| line.chars().count()) as u16;
+    let height = lines.len() as u16;
+
+    if clear {
+        queue!(out, terminal::Clear(ClearType::All))?;
+    }
+
+    if width > columns || height > rows {
+        queue!(
+            out,
+            cursor::MoveTo(0, 0),
+            Print(format!(
+                "Terminal is too small, resize it to at least {}x{}.",
+                width, height
+            ))
+        )?;
+    } else {
+        let x = (columns - width) / 2;
+        let y = (rows - height) / 2;
+
+        for (i, line) in lines.iter().enumerate() {
+            queue!(out, cursor::MoveTo(x, y + i as u16), Print(line))?;
+        }
+    }
+
+    out.flush()
+}
+
+/// Run the interpreter until Escape or Ctrl+C is pressed.
+fn run(cpu: &mut ferrous::Ferrous) -> crossterm::Result<()> {
+    let _terminal = RawTerminal::enter()?;
+    let mut out = io::stdout();
+
+    // Frames left until each key is released.
+    let mut held = [0u8; 16];
+
+    // Lines on screen, only redrawn when they change or the terminal is resized.
+    let mut drawn = Vec::new();
+    let mut resized = true;
+
+    let mut next_frame = Instant::now();
+
+    loop {
+        // Handle input until the next frame is due.
+        while event::poll(next_frame.saturating_duration_since(Instant::now()))? {
+            match event::read()? {
+                Event::Key(KeyEvent {
+                    code: KeyCode::Esc, ..
+                }) => return Ok(()),
+
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('c'),
+                    modifiers,
+                }) if modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char(c),
+                    ..
+                }) => {
+                    let c = c.to_ascii_lowercase();
+
+                    if let Some(index) = KEYMAP.iter().position(|&key| key == c) {
+                        // Repeats of a held key aren't new presses.
+                        if held[index] == 0 {
+                            cpu.queue_key_press(index as u8);
+                        }
+
+                        held[index] = KEY_HOLD_FRAMES;
+                    }
+                }
+
+                Event::Resize(..) => resized = true,
+
+                _ => {}
+            }
+        }
+
+        // Don't try to catch up after falling behind, e.g. when suspended.
+        next_frame = (next_frame + FRAME_TIME).max(Instant::now());
+
+        for (index, frames) in held.iter_mut().enumerate() {
+            cpu.set_key_at_index(index, *frames > 0);
+            *frames = frames.saturating_sub(1);
+        }
+
+        // Invalid opcodes are skipped silently, as logging would garble the screen.
+        cpu.advance_frame(CYCLES_PER_FRAME);
+
+        let lines = render_lines(cpu);
+
+        if resized || lines != drawn {
+            // Changing the resolution changes the size of the screen.
+            let clear = resized || lines.len() != drawn.len();
+            draw(&mut out, &lines, clear)?;
+
+            drawn = lines;
+            resized = false;
+        }
+    }
+}
+
+fn main() {
+    let path = match std::env::args_os().nth(1) {
+        Some(path) => PathBuf::from(path),
+        None => {
+            eprintln!("Usage: ferrous-tui <ROM>");
+            std::process::exit(1);
+        }
+    };
+
+    let data = match std::fs::read(&path) {
+        Ok(data) => data,
+        Err(error) => {
+            eprintln!("[ERROR] failed to read {}, {}", path.display(), error);
+            std::process::exit(1);
+        }
+    };
+
+    let mut cpu = ferrous::Ferrous::new();
+
+    if let Err(error) = cpu.load_rom(&data) {
+        eprintln!("[ERROR] failed to load {}, {}", path.display(), error);
+        std::process::exit(1);
+    }
+
+    if let Err(error) = run(&mut cpu) {
+        eprintln!("[ERROR] terminal error, {}", error);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,62 @@
+//! Contains a clock that paces emulation at a fixed 60 ticks per
+//! second, independent of the rate a frontend draws frames at.
+
+use core::time::Duration;
+
+/// Rate the timers count down at, in ticks per second.
+pub const TICK_RATE: u32 = 60;
+
+/// Duration of a single tick.
+pub const TICK_TIME: Duration = Duration::from_nanos(1_000_000_000 / TICK_RATE as u64);
+
+/// Accumulates the time that passed between frames, and hands it out as
+/// whole 60 Hz ticks. Each tick executes a share of the instructions per
+/// second, then steps the timers once, see `Ferrous::advance_frame`.
+///
+/// A frontend drawing at 144 Hz runs a tick on some frames and none on
+/// others, so neither the timers nor the CPU run fast.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TickClock {
+    /// Time that passed, and hasn't been handed out as ticks yet.
+    elapsed: Duration,
+
+    /// Instructions carried over from earlier ticks, in
+    /// sixtieths, so that any instructions per second are met.
+    carry: u32,
+}
+
+impl TickClock {
+    /// Create a clock with no time accumulated.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the time that passed since the last frame.
+    pub fn advance(&mut self, delta: Duration) {
+        self.elapsed += delta;
+    }
+
+    /// Discard the accumulated time, e.g. while paused,
+    /// so that it isn't caught up on after resuming.
+    pub fn reset(&mut self) {
+        self.elapsed = Duration::ZERO;
+        self.carry = 0;
+    }
+
+    /// Take the next tick that is due, returning the cycles to execute
+    /// in it at the given instructions per second.
+    ///
+    /// Returns `None` once less than a tick is accumulated.
+    pub fn next_tick(&mut self, instructions_per_second: u32) -> Option<u16> {
+        if self.elapsed < TICK_TIME {
+            return None;
+        }
+
+        self.elapsed -= TICK_TIME;
+
+        let budget = instructions_per_second.saturating_add(self.carry);
+        self.carry = budget % TICK_RATE;
+
+        Some((budget / TICK_RATE).min(u16::MAX as u32) as u16)
+    }
+}
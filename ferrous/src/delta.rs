@@ -0,0 +1,71 @@
+//! Contains descriptions of what a single instruction changed,
+//! see `Ferrous::execute_cycle_traced`.
+
+use alloc::vec::Vec;
+
+/// Everything a single cycle changed, each as its old and new value.
+///
+/// Only values that actually changed are listed, so e.g.
+/// a register overwritten with its own value is left out.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CycleDelta {
+    /// The opcode that was executed, `None` if the interpreter had
+    /// halted or the opcode was invalid, as with `execute_cycle`.
+    pub opcode: Option<u16>,
+
+    /// Program counter before and after the cycle.
+    pub pc: (usize, usize),
+
+    /// Changed general purpose registers, as (index, old, new).
+    /// Flags set by arithmetic show up as changes to VF.
+    pub registers: Vec<(usize, u8, u8)>,
+
+    /// Index register, if it changed.
+    pub index: Option<(usize, usize)>,
+
+    /// Stack pointer, if it changed.
+    pub stack_pointer: Option<(usize, usize)>,
+
+    /// Changed return address stack entries, as (index, old, new).
+    pub stack: Vec<(usize, u16, u16)>,
+
+    /// Delay timer, if it changed.
+    pub delay_timer: Option<(u8, u8)>,
+
+    /// Sound timer, if it changed.
+    pub sound_timer: Option<(u8, u8)>,
+
+    /// Changed bytes of memory, as (address, old, new).
+    pub memory: Vec<(usize, u8, u8)>,
+
+    /// Changed Super Chip flag registers, as (index, old, new).
+    pub flag_registers: Vec<(usize, u8, u8)>,
+
+    /// Indices of the pixels that were flipped, into the video buffer.
+    pub pixels: Vec<usize>,
+
+    /// High resolution mode, if it was switched.
+    pub highres: Option<(bool, bool)>,
+
+    /// Did the cycle halt the interpreter?
+    pub halted: bool,
+}
+
+/// Get the old and new value, if they differ.
+pub(crate) fn changed<T: PartialEq>(old: T, new: T) -> Option<(T, T)> {
+    if old != new {
+        Some((old, new))
+    } else {
+        None
+    }
+}
+
+/// Get the elements that differ between the two slices, as (index, old, new).
+pub(crate) fn changes<T: Copy + PartialEq>(old: &[T], new: &[T]) -> Vec<(usize, T, T)> {
+    old.iter()
+        .zip(new)
+        .enumerate()
+        .filter(|(_, (old, new))| old != new)
+        .map(|(index, (&old, &new))| (index, old, new))
+        .collect()
+}
@@ -0,0 +1,533 @@
+//! Contains a simple disassembler for (super) Chip-8 opcodes.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// A decoded (super) Chip-8 instruction.
+///
+/// Register operands are stored as indices (0x0..=0xF),
+/// and addresses as 12-bit values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    /// 00Cn - SCD nibble
+    Scd(u8),
+    /// 00E0 - CLS
+    Cls,
+    /// 00EE - RET
+    Ret,
+    /// 00FB - SCR
+    Scr,
+    /// 00FC - SCL
+    Scl,
+    /// 00FD - EXIT
+    Exit,
+    /// 00FE - LOW
+    Low,
+    /// 00FF - HIGH
+    High,
+    /// 1nnn - JP addr
+    Jp(u16),
+    /// 2nnn - CALL addr
+    Call(u16),
+    /// 3xkk - SE Vx, byte
+    SeByte(u8, u8),
+    /// 4xkk - SNE Vx, byte
+    SneByte(u8, u8),
+    /// 5xy0 - SE Vx, Vy
+    SeReg(u8, u8),
+    /// 6xkk - LD Vx, byte
+    LdByte(u8, u8),
+    /// 7xkk - ADD Vx, byte
+    AddByte(u8, u8),
+    /// 8xy0 - LD Vx, Vy
+    LdReg(u8, u8),
+    /// 8xy1 - OR Vx, Vy
+    Or(u8, u8),
+    /// 8xy2 - AND Vx, Vy
+    And(u8, u8),
+    /// 8xy3 - XOR Vx, Vy
+    Xor(u8, u8),
+    /// 8xy4 - ADD Vx, Vy
+    AddReg(u8, u8),
+    /// 8xy5 - SUB Vx, Vy
+    Sub(u8, u8),
+    /// 8xy6 - SHR Vx {, Vy}
+    Shr(u8, u8),
+    /// 8xy7 - SUBN Vx, Vy
+    Subn(u8, u8),
+    /// 8xyE - SHL Vx {, Vy}
+    Shl(u8, u8),
+    /// 9xy0 - SNE Vx, Vy
+    SneReg(u8, u8),
+    /// Annn - LD I, addr
+    LdI(u16),
+    /// Bnnn - JP V0, addr
+    JpV0(u16),
+    /// Cxkk - RND Vx, byte
+    Rnd(u8, u8),
+    /// Dxyn - DRW Vx, Vy, nibble
+    Drw(u8, u8, u8),
+    /// Ex9E - SKP Vx
+    Skp(u8),
+    /// ExA1 - SKNP Vx
+    Sknp(u8),
+    /// Fx07 - LD Vx, DT
+    LdVxDt(u8),
+    /// Fx0A - LD Vx, K
+    LdVxK(u8),
+    /// Fx15 - LD DT, Vx
+    LdDtVx(u8),
+    /// Fx18 - LD ST, Vx
+    LdStVx(u8),
+    /// Fx1E - ADD I, Vx
+    AddI(u8),
+    /// Fx29 - LD F, Vx
+    LdF(u8),
+    /// Fx30 - LD HF, Vx
+    LdHf(u8),
+    /// Fx33 - LD B, Vx
+    LdB(u8),
+    /// Fx55 - LD [I], Vx
+    LdIVx(u8),
+    /// Fx65 - LD Vx, [I]
+    LdVxI(u8),
+    /// Fx75 - LD R, Vx
+    LdRVx(u8),
+    /// Fx85 - LD Vx, R
+    LdVxR(u8),
+}
+
+/// Broad group an instruction belongs to, for highlighting disassembly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    /// Jumps, calls, returns and conditional skips.
+    Flow,
+    /// Loads and stores between registers, I and memory.
+    Load,
+    /// Arithmetic, logic and random numbers.
+    Arithmetic,
+    /// Drawing, clearing, scrolling and changing the resolution.
+    Draw,
+    /// Keypad and timer access.
+    Io,
+}
+
+impl Instruction {
+    /// Get the category of the instruction.
+    pub fn category(&self) -> Category {
+        match self {
+            Self::Ret
+            | Self::Exit
+            | Self::Jp(_)
+            | Self::Call(_)
+            | Self::SeByte(..)
+            | Self::SneByte(..)
+            | Self::SeReg(..)
+            | Self::SneReg(..)
+            | Self::JpV0(_) => Category::Flow,
+
+            Self::LdByte(..)
+            | Self::LdReg(..)
+            | Self::LdI(_)
+            | Self::LdF(_)
+            | Self::LdHf(_)
+            | Self::LdB(_)
+            | Self::LdIVx(_)
+            | Self::LdVxI(_)
+            | Self::LdRVx(_)
+            | Self::LdVxR(_) => Category::Load,
+
+            Self::AddByte(..)
+            | Self::Or(..)
+            | Self::And(..)
+            | Self::Xor(..)
+            | Self::AddReg(..)
+            | Self::Sub(..)
+            | Self::Shr(..)
+            | Self::Subn(..)
+            | Self::Shl(..)
+            | Self::AddI(_)
+            | Self::Rnd(..) => Category::Arithmetic,
+
+            Self::Scd(_)
+            | Self::Cls
+            | Self::Scr
+            | Self::Scl
+            | Self::Low
+            | Self::High
+            | Self::Drw(..) => Category::Draw,
+
+            Self::Skp(_)
+            | Self::Sknp(_)
+            | Self::LdVxDt(_)
+            | Self::LdVxK(_)
+            | Self::LdDtVx(_)
+            | Self::LdStVx(_) => Category::Io,
+        }
+    }
+
+    /// Get the form of the instruction, its mnemonic with the
+    /// operands named rather than filled in, e.g. `LD Vx, byte`.
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Self::Scd(_) => "SCD nibble",
+            Self::Cls => "CLS",
+            Self::Ret => "RET",
+            Self::Scr => "SCR",
+            Self::Scl => "SCL",
+            Self::Exit => "EXIT",
+            Self::Low => "LOW",
+            Self::High => "HIGH",
+            Self::Jp(_) => "JP addr",
+            Self::Call(_) => "CALL addr",
+            Self::SeByte(..) => "SE Vx, byte",
+            Self::SneByte(..) => "SNE Vx, byte",
+            Self::SeReg(..) => "SE Vx, Vy",
+            Self::LdByte(..) => "LD Vx, byte",
+            Self::AddByte(..) => "ADD Vx, byte",
+            Self::LdReg(..) => "LD Vx, Vy",
+            Self::Or(..) => "OR Vx, Vy",
+            Self::And(..) => "AND Vx, Vy",
+            Self::Xor(..) => "XOR Vx, Vy",
+            Self::AddReg(..) => "ADD Vx, Vy",
+            Self::Sub(..) => "SUB Vx, Vy",
+            Self::Shr(..) => "SHR Vx, Vy",
+            Self::Subn(..) => "SUBN Vx, Vy",
+            Self::Shl(..) => "SHL Vx, Vy",
+            Self::SneReg(..) => "SNE Vx, Vy",
+            Self::LdI(_) => "LD I, addr",
+            Self::JpV0(_) => "JP V0, addr",
+            Self::Rnd(..) => "RND Vx, byte",
+            Self::Drw(..) => "DRW Vx, Vy, nibble",
+            Self::Skp(_) => "SKP Vx",
+            Self::Sknp(_) => "SKNP Vx",
+            Self::LdVxDt(_) => "LD Vx, DT",
+            Self::LdVxK(_) => "LD Vx, K",
+            Self::LdDtVx(_) => "LD DT, Vx",
+            Self::LdStVx(_) => "LD ST, Vx",
+            Self::AddI(_) => "ADD I, Vx",
+            Self::LdF(_) => "LD F, Vx",
+            Self::LdHf(_) => "LD HF, Vx",
+            Self::LdB(_) => "LD B, Vx",
+            Self::LdIVx(_) => "LD [I], Vx",
+            Self::LdVxI(_) => "LD Vx, [I]",
+            Self::LdRVx(_) => "LD R, Vx",
+            Self::LdVxR(_) => "LD Vx, R",
+        }
+    }
+
+    /// Decode the given opcode into an instruction.
+    ///
+    /// Returns `None` if the opcode is unknown or invalid.
+    pub fn decode(opcode: u16) -> Option<Self> {
+        let bytes = opcode.to_be_bytes();
+
+        let nibbles = (
+            (bytes[0] & 0xF0) >> 4,
+            (bytes[0] & 0x0F),
+            (bytes[1] & 0xF0) >> 4,
+            (bytes[1] & 0x0F),
+        );
+
+        let x = nibbles.1;
+        let y = nibbles.2;
+        let n = nibbles.3;
+
+        let kk = bytes[1];
+        let nnn = opcode & 0x0FFF;
+
+        let instruction = match nibbles {
+            // 0x0000 - 0x1000
+            (0x0, 0x0, 0xC, _) => Self::Scd(n),
+            (0x0, 0x0, 0xE, 0x0) => Self::Cls,
+            (0x0, 0x0, 0xE, 0xE) => Self::Ret,
+            (0x0, 0x0, 0xF, 0xB) => Self::Scr,
+            (0x0, 0x0, 0xF, 0xC) => Self::Scl,
+            (0x0, 0x0, 0xF, 0xD) => Self::Exit,
+            (0x0, 0x0, 0xF, 0xE) => Self::Low,
+            (0x0, 0x0, 0xF, 0xF) => Self::High,
+
+            // 0x1000 - 0x8000
+            (0x1, _, _, _) => Self::Jp(nnn),
+            (0x2, _, _, _) => Self::Call(nnn),
+            (0x3, _, _, _) => Self::SeByte(x, kk),
+            (0x4, _, _, _) => Self::SneByte(x, kk),
+            (0x5, _, _, 0) => Self::SeReg(x, y),
+            (0x6, _, _, _) => Self::LdByte(x, kk),
+            (0x7, _, _, _) => Self::AddByte(x, kk),
+
+            // 0x8000 - 0x9000
+            (0x8, _, _, 0x0) => Self::LdReg(x, y),
+            (0x8, _, _, 0x1) => Self::Or(x, y),
+            (0x8, _, _, 0x2) => Self::And(x, y),
+            (0x8, _, _, 0x3) => Self::Xor(x, y),
+            (0x8, _, _, 0x4) => Self::AddReg(x, y),
+            (0x8, _, _, 0x5) => Self::Sub(x, y),
+            (0x8, _, _, 0x6) => Self::Shr(x, y),
+            (0x8, _, _, 0x7) => Self::Subn(x, y),
+            (0x8, _, _, 0xE) => Self::Shl(x, y),
+
+            // 0x9000 - 0xA000
+            (0x9, _, _, 0) => Self::SneReg(x, y),
+
+            // 0xA000 - 0xC000
+            (0xA, _, _, _) => Self::LdI(nnn),
+            (0xB, _, _, _) => Self::JpV0(nnn),
+
+            // 0xC000 - 0xD000
+            (0xC, _, _, _) => Self::Rnd(x, kk),
+
+            // 0xD000 - 0xE000
+            (0xD, _, _, _) => Self::Drw(x, y, n),
+
+            // 0xE000 - 0xF000
+            (0xE, _, 0x9, 0xE) => Self::Skp(x),
+            (0xE, _, 0xA, 0x1) => Self::Sknp(x),
+
+            // 0xF000
+            (0xF, _, 0x0, 0x7) => Self::LdVxDt(x),
+            (0xF, _, 0x0, 0xA) => Self::LdVxK(x),
+            (0xF, _, 0x1, 0x5) => Self::LdDtVx(x),
+            (0xF, _, 0x1, 0x8) => Self::LdStVx(x),
+            (0xF, _, 0x1, 0xE) => Self::AddI(x),
+            (0xF, _, 0x2, 0x9) => Self::LdF(x),
+            (0xF, _, 0x3, 0x0) => Self::LdHf(x),
+            (0xF, _, 0x3, 0x3) => Self::LdB(x),
+            (0xF, _, 0x5, 0x5) => Self::LdIVx(x),
+            (0xF, _, 0x6, 0x5) => Self::LdVxI(x),
+            (0xF, _, 0x7, 0x5) => Self::LdRVx(x),
+            (0xF, _, 0x8, 0x5) => Self::LdVxR(x),
+
+            // Unknown/Invalid opcodes
+            _ => return None,
+        };
+
+        Some(instruction)
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Scd(n) => write!(f, "SCD {:#X}", n),
+            Self::Cls => write!(f, "CLS"),
+            Self::Ret => write!(f, "RET"),
+            Self::Scr => write!(f, "SCR"),
+            Self::Scl => write!(f, "SCL"),
+            Self::Exit => write!(f, "EXIT"),
+            Self::Low => write!(f, "LOW"),
+            Self::High => write!(f, "HIGH"),
+            Self::Jp(nnn) => write!(f, "JP {:#05X}", nnn),
+            Self::Call(nnn) => write!(f, "CALL {:#05X}", nnn),
+            Self::SeByte(x, kk) => write!(f, "SE V{:X}, {:#04X}", x, kk),
+            Self::SneByte(x, kk) => write!(f, "SNE V{:X}, {:#04X}", x, kk),
+            Self::SeReg(x, y) => write!(f, "SE V{:X}, V{:X}", x, y),
+            Self::LdByte(x, kk) => write!(f, "LD V{:X}, {:#04X}", x, kk),
+            Self::AddByte(x, kk) => write!(f, "ADD V{:X}, {:#04X}", x, kk),
+            Self::LdReg(x, y) => write!(f, "LD V{:X}, V{:X}", x, y),
+            Self::Or(x, y) => write!(f, "OR V{:X}, V{:X}", x, y),
+            Self::And(x, y) => write!(f, "AND V{:X}, V{:X}", x, y),
+            Self::Xor(x, y) => write!(f, "XOR V{:X}, V{:X}", x, y),
+            Self::AddReg(x, y) => write!(f, "ADD V{:X}, V{:X}", x, y),
+            Self::Sub(x, y) => write!(f, "SUB V{:X}, V{:X}", x, y),
+            Self::Shr(x, y) => write!(f, "SHR V{:X}, V{:X}", x, y),
+            Self::Subn(x, y) => write!(f, "SUBN V{:X}, V{:X}", x, y),
+            Self::Shl(x, y) => write!(f, "SHL V{:X}, V{:X}", x, y),
+            Self::SneReg(x, y) => write!(f, "SNE V{:X}, V{:X}", x, y),
+            Self::LdI(nnn) => write!(f, "LD I, {:#05X}", nnn),
+            Self::JpV0(nnn) => write!(f, "JP V0, {:#05X}", nnn),
+            Self::Rnd(x, kk) => write!(f, "RND V{:X}, {:#04X}", x, kk),
+            Self::Drw(x, y, n) => write!(f, "DRW V{:X}, V{:X}, {:#X}", x, y, n),
+            Self::Skp(x) => write!(f, "SKP V{:X}", x),
+            Self::Sknp(x) => write!(f, "SKNP V{:X}", x),
+            Self::LdVxDt(x) => write!(f, "LD V{:X}, DT", x),
+            Self::LdVxK(x) => write!(f, "LD V{:X}, K", x),
+            Self::LdDtVx(x) => write!(f, "LD DT, V{:X}", x),
+            Self::LdStVx(x) => write!(f, "LD ST, V{:X}", x),
+            Self::AddI(x) => write!(f, "ADD I, V{:X}", x),
+            Self::LdF(x) => write!(f, "LD F, V{:X}", x),
+            Self::LdHf(x) => write!(f, "LD HF, V{:X}", x),
+            Self::LdB(x) => write!(f, "LD B, V{:X}", x),
+            Self::LdIVx(x) => write!(f, "LD [I], V{:X}", x),
+            Self::LdVxI(x) => write!(f, "LD V{:X}, [I]", x),
+            Self::LdRVx(x) => write!(f, "LD R, V{:X}", x),
+            Self::LdVxR(x) => write!(f, "LD V{:X}, R", x),
+        }
+    }
+}
+
+/// Disassemble a single opcode into its mnemonic form.
+///
+/// Unknown or invalid opcodes are rendered as a raw data word.
+pub fn disassemble(opcode: u16) -> String {
+    match Instruction::decode(opcode) {
+        Some(instruction) => instruction.to_string(),
+        None => format!("DW {:#06X}", opcode),
+    }
+}
+
+/// A single entry of a `Listing`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListingItem {
+    /// An instruction that is reachable from the entry point.
+    Code {
+        address: usize,
+        opcode: u16,
+        instruction: Instruction,
+    },
+
+    /// A byte that isn't reachable as code, like sprites.
+    Data { address: usize, byte: u8 },
+}
+
+/// Disassembly of a program, with code and data told apart,
+/// see `trace_disassemble`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Listing {
+    /// Items in address order, covering every byte from the entry point on.
+    pub items: Vec<ListingItem>,
+
+    /// Addresses that are jumped to or called, and get a label.
+    pub labels: BTreeSet<usize>,
+}
+
+impl fmt::Display for Listing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for item in &self.items {
+            match *item {
+                ListingItem::Code {
+                    address,
+                    opcode,
+                    instruction,
+                } => {
+                    if self.labels.contains(&address) {
+                        writeln!(f, "label_{:#05X}:", address)?;
+                    }
+
+                    writeln!(f, "    {:#05X}  {:04X}  {}", address, opcode, instruction)?;
+                }
+
+                ListingItem::Data { address, byte } => {
+                    writeln!(f, "    {:#05X}  {:02X}    DB {:#04X}", address, byte, byte)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Disassemble the program in memory by following its control flow
+/// from the entry point, rather than sweeping it linearly, so that
+/// sprites and other data aren't mistaken for code.
+///
+/// Jumps and calls are followed to their targets, and both paths of
+/// conditional skips are followed. `JP V0, addr` depends on V0, so only
+/// its base address is followed, any other targets it can reach are
+/// reported as data.
+///
+/// The listing covers the entry point up to the end of `memory`,
+/// so pass memory only up to the end of the program.
+pub fn trace_disassemble(memory: &[u8], entry: usize) -> Listing {
+    let in_range = |address: usize| address >= entry && address + 1 < memory.len();
+
+    let mut is_code = vec![false; memory.len()];
+    let mut labels = BTreeSet::new();
+    let mut pending = vec![entry];
+
+    while let Some(mut address) = pending.pop() {
+        while in_range(address) && !is_code[address] {
+            let opcode = u16::from_be_bytes([memory[address], memory[address + 1]]);
+
+            let instruction = match Instruction::decode(opcode) {
+                Some(instruction) => instruction,
+                None => break,
+            };
+
+            is_code[address] = true;
+
+            match instruction {
+                Instruction::Jp(target) | Instruction::JpV0(target) => {
+                    let target = target as usize;
+
+                    if in_range(target) {
+                        labels.insert(target);
+                        pending.push(target);
+                    }
+
+                    break;
+                }
+
+                Instruction::Call(target) => {
+                    let target = target as usize;
+
+                    if in_range(target) {
+                        labels.insert(target);
+                        pending.push(target);
+                    }
+                }
+
+                Instruction::Ret | Instruction::Exit => break,
+
+                Instruction::SeByte(..)
+                | Instruction::SneByte(..)
+                | Instruction::SeReg(..)
+                | Instruction::SneReg(..)
+                | Instruction::Skp(_)
+                | Instruction::Sknp(_) => pending.push(address + 4),
+
+                _ => {}
+            }
+
+            address += 2;
+        }
+    }
+
+    let mut items = Vec::new();
+    let mut address = entry;
+
+    while address < memory.len() {
+        if is_code[address] {
+            let opcode = u16::from_be_bytes([memory[address], memory[address + 1]]);
+
+            items.push(ListingItem::Code {
+                address,
+                opcode,
+                instruction: Instruction::decode(opcode).unwrap(),
+            });
+
+            address += 2;
+        } else {
+            items.push(ListingItem::Data {
+                address,
+                byte: memory[address],
+            });
+
+            address += 1;
+        }
+    }
+
+    Listing { items, labels }
+}
+
+/// Count how many times each instruction form, see `Instruction::mnemonic`,
+/// appears in the program, to get a feel for what the program spends its
+/// code on, e.g. drawing or arithmetic.
+///
+/// This is a static count over the code found by `trace_disassemble`, so
+/// data isn't counted, and neither is how often an instruction is executed.
+pub fn opcode_histogram(memory: &[u8], entry: usize) -> BTreeMap<&'static str, u32> {
+    let mut histogram = BTreeMap::new();
+
+    for item in trace_disassemble(memory, entry).items {
+        if let ListingItem::Code { instruction, .. } = item {
+            *histogram.entry(instruction.mnemonic()).or_insert(0) += 1;
+        }
+    }
+
+    histogram
+}
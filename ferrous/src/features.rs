@@ -0,0 +1,82 @@
+//! Contains detection of the platform features a ROM uses.
+
+use crate::disasm::Instruction;
+
+/// Platform features used by a ROM, see `Ferrous::detect_feature_set`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FeatureSet {
+    /// Uses Super Chip instructions.
+    pub super_chip: bool,
+
+    /// Uses XO-Chip instructions, which this interpreter doesn't support.
+    pub xo_chip: bool,
+
+    /// Switches to the 128x64 high resolution mode.
+    pub high_res: bool,
+}
+
+impl FeatureSet {
+    /// Scan the ROM for opcodes that are specific to a platform.
+    ///
+    /// Every two byte aligned word is treated as an opcode, so sprite
+    /// data that happens to look like one of these opcodes is
+    /// reported as well.
+    pub fn detect(rom: &[u8]) -> Self {
+        let mut features = Self::default();
+
+        for word in rom.chunks_exact(2) {
+            let opcode = u16::from_be_bytes([word[0], word[1]]);
+
+            match Instruction::decode(opcode) {
+                Some(Instruction::High) => {
+                    features.super_chip = true;
+                    features.high_res = true;
+                }
+
+                Some(
+                    Instruction::Scd(_)
+                    | Instruction::Scr
+                    | Instruction::Scl
+                    | Instruction::Exit
+                    | Instruction::Low
+                    | Instruction::LdHf(_)
+                    | Instruction::LdRVx(_)
+                    | Instruction::LdVxR(_),
+                ) => features.super_chip = true,
+
+                Some(_) => {}
+
+                None => features.xo_chip |= is_xo_chip_opcode(opcode),
+            }
+        }
+
+        features
+    }
+}
+
+/// Is the opcode one of the XO-Chip extensions?
+fn is_xo_chip_opcode(opcode: u16) -> bool {
+    let bytes = opcode.to_be_bytes();
+
+    let nibbles = (
+        (bytes[0] & 0xF0) >> 4,
+        (bytes[0] & 0x0F),
+        (bytes[1] & 0xF0) >> 4,
+        (bytes[1] & 0x0F),
+    );
+
+    matches!(
+        nibbles,
+        // 00Dn - SCROLL-UP n
+        (0x0, 0x0, 0xD, _)
+        // 5xy2 - SAVE Vx - Vy, 5xy3 - LOAD Vx - Vy
+        | (0x5, _, _, 0x2)
+        | (0x5, _, _, 0x3)
+        // F000 - LD I, long, F002 - AUDIO
+        | (0xF, 0x0, 0x0, 0x0)
+        | (0xF, 0x0, 0x0, 0x2)
+        // Fn01 - PLANE n, Fx3A - PITCH Vx
+        | (0xF, _, 0x0, 0x1)
+        | (0xF, _, 0x3, 0xA)
+    )
+}
@@ -6,19 +6,264 @@ use serde::{Deserialize, Serialize};
 #[cfg(feature = "savestates")]
 use serde_big_array::BigArray;
 
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::ops::{Index, IndexMut, Range};
+
+use crate::clock::TICK_RATE;
+use crate::delta::{changed, changes, CycleDelta};
+use crate::disasm::{disassemble, opcode_histogram};
+use crate::features::FeatureSet;
 use crate::font::*;
+use crate::lockstep::{InputRecording, Lockstep};
+use crate::quirks::{LoadStoreMode, QuirkProfile};
+use crate::resolution::Resolution;
+use crate::snapshot::DisplaySnapshot;
+use crate::trace::{Divergence, TraceEntry};
+
+/// What the interpreter should do when it encounters
+/// an invalid or unknown opcode.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "savestates", derive(Serialize, Deserialize))]
+pub enum InvalidOpcodePolicy {
+    /// Skip the opcode, and continue execution.
+    #[default]
+    Ignore,
+
+    /// Halt the interpreter, as if `00FD` was executed.
+    Halt,
+
+    /// Panic with the opcode and its address.
+    Panic,
+}
+
+/// Errors raised by subroutine calls and returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "savestates", derive(Serialize, Deserialize))]
+pub enum StackError {
+    /// `2nnn` was executed with all sixteen stack entries in use.
+    Overflow,
+
+    /// `00EE` was executed with an empty stack.
+    Underflow,
+}
+
+/// Why `Ferrous::step_n` stopped before executing all the cycles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// The interpreter is halted, see `is_halted` and `stack_error`.
+    Halted,
+
+    /// The program counter reached a breakpoint at the given address.
+    Breakpoint(usize),
+
+    /// An invalid or unknown opcode was encountered.
+    InvalidOpcode(u16),
+}
+
+/// Result of `Ferrous::step_n`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepSummary {
+    /// Number of cycles that were executed.
+    pub executed: usize,
+
+    /// Why execution stopped early, if it did.
+    pub stopped: Option<StopReason>,
+}
+
+/// What a region of memory is used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryRegion {
+    /// The low resolution font sprites, 0x000 to 0x04F.
+    LowResFont,
+
+    /// The high resolution font sprites, 0x050 to 0x0EF.
+    HighResFont,
+
+    /// Unused memory reserved for the interpreter, up to the entry point.
+    Reserved,
+
+    /// The bytes of the loaded ROM, from the entry point.
+    Program,
+
+    /// Memory after the ROM, free for the program to use.
+    Data,
+}
+
+/// Size of the VRAM in bytes, one byte per pixel of the largest resolution.
+pub const VRAM_SIZE: usize = Resolution::MAX.pixels();
+
+/// Maximum number of events kept, if the frontend doesn't drain them.
+const EVENT_QUEUE_LIMIT: usize = 64;
+
+/// Pixels the display is scrolled by horizontally, by 00FB and 00FC.
+const SCROLL_PIXELS: usize = 4;
+
+/// The pixels of each sprite byte as eight VRAM bytes, leftmost first,
+/// so that a whole byte of a sprite can be drawn with a single XOR.
+const PIXEL_MASKS: [u64; 256] = {
+    let mut masks = [0; 256];
+    let mut byte = 0;
+
+    while byte < 256 {
+        let mut bit = 0;
+
+        while bit < 8 {
+            if byte & (0x80 >> bit) != 0 {
+                masks[byte] |= 1 << (bit * 8);
+            }
+
+            bit += 1;
+        }
+
+        byte += 1;
+    }
+
+    masks
+};
+
+/// Entry point of savestates made before it was configurable.
+#[cfg(feature = "savestates")]
+fn default_entry_point() -> usize {
+    0x200
+}
+
+/// Sprites wrapped in both directions before it was configurable.
+#[cfg(feature = "savestates")]
+fn default_wrap() -> bool {
+    true
+}
+
+/// Get the load/store mode the old load/store quirk stood for.
+/// When enabled I is left unchanged, otherwise it is incremented by X + 1.
+fn load_store_mode_from_quirk(quirk: bool) -> LoadStoreMode {
+    if quirk {
+        LoadStoreMode::Unchanged
+    } else {
+        LoadStoreMode::IncrementByXPlus1
+    }
+}
+
+/// Deserialize the load/store mode, or the `load_store_quirk`
+/// boolean it replaced in savestates made before it had modes.
+#[cfg(feature = "savestates")]
+fn deserialize_load_store_mode<'de, D>(deserializer: D) -> Result<LoadStoreMode, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::de::{self, IntoDeserializer, Visitor};
+
+    struct ModeVisitor;
+
+    impl<'de> Visitor<'de> for ModeVisitor {
+        type Value = LoadStoreMode;
+
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            formatter.write_str("a load/store mode, or the old load/store quirk")
+        }
+
+        fn visit_bool<E: de::Error>(self, quirk: bool) -> Result<Self::Value, E> {
+            Ok(load_store_mode_from_quirk(quirk))
+        }
+
+        fn visit_str<E: de::Error>(self, mode: &str) -> Result<Self::Value, E> {
+            LoadStoreMode::deserialize(mode.into_deserializer())
+        }
+    }
+
+    // Only self-describing formats can tell the two apart, the old
+    // field was never written in any other format by the frontends.
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_any(ModeVisitor)
+    } else {
+        LoadStoreMode::deserialize(deserializer)
+    }
+}
+
+/// Ten instructions per 60 Hz tick.
+fn default_instructions_per_second() -> u32 {
+    600
+}
+
+/// Hash the given bytes with 64 bit FNV-1a.
+fn fnv1a<'a>(bytes: impl IntoIterator<Item = &'a u8>) -> u64 {
+    bytes
+        .into_iter()
+        .fold(0xCBF2_9CE4_8422_2325, |hash, &byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01B3)
+        })
+}
+
+/// Events emitted by the interpreter, see `Ferrous::drain_events`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CpuEvent {
+    /// The beep started playing.
+    SoundOn,
+
+    /// The beep stopped playing.
+    SoundOff,
+
+    /// The interpreter switched between low and high resolution mode.
+    ModeChange { is_highres: bool },
+
+    /// The interpreter halted.
+    Halt,
+}
+
+/// Changes to the display, see `Ferrous::drain_draw_events`.
+///
+/// Replaying them in order with `Ferrous::apply_draw_event` on another
+/// interpreter, whose display started out the same, reproduces the display.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "savestates", derive(Serialize, Deserialize))]
+pub enum DrawEvent {
+    /// The display was cleared.
+    Clear,
+
+    /// A sprite was drawn at (x, y), before wrapping around the display.
+    Sprite {
+        x: u8,
+        y: u8,
+
+        /// The rows of the sprite, two bytes per row if it is wide.
+        bytes: Vec<u8>,
+
+        /// Is this a 16x16 Super Chip sprite?
+        wide: bool,
+
+        /// Did the sprite erase any lit pixels?
+        collision: bool,
+    },
+
+    /// The display was scrolled down by the given number of lines.
+    ScrollDown(u8),
+
+    /// The display was scrolled 4 pixels right.
+    ScrollRight,
+
+    /// The display was scrolled 4 pixels left.
+    ScrollLeft,
+
+    /// The interpreter switched between low and high resolution mode.
+    Resolution { is_highres: bool },
+}
 
 /// Implementation of a (super) Chip-8 interpreter.
 ///
 /// # Example
 ///
 /// ```rust
-/// use ch8_core::CPU;
+/// use ferrous::CPU;
 ///
 /// let mut cpu = CPU::new();
 ///
 /// // Load ROM, handle display, audio and input.
 /// ```
+///
+/// The interpreter is plain data, so it is `Send` and can be
+/// moved to a dedicated emulation thread.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "savestates", derive(Serialize, Deserialize))]
 pub struct Ferrous {
@@ -52,12 +297,17 @@ pub struct Ferrous {
     /// It is also decremented at a rate of 60Hz when non-zero.
     pub st: u8,
 
-    /// Video RAM; Used to store the current state of the 64 * 32 pixels
-    /// screen.
+    /// Was the sound timer non-zero at the start of the last timer tick?
+    /// Keeps short beeps audible for a full tick.
+    #[cfg_attr(feature = "savestates", serde(default))]
+    beep_latch: bool,
+
+    /// Video RAM; Used to store the current state of the screen,
+    /// laid out as described by `Resolution`.
     /// Each byte represents an individual pixel, where 1 means ON (White)
     /// and 0 means OFF (Black).
     #[cfg_attr(feature = "savestates", serde(with = "BigArray"))]
-    pub vram: [u8; 0x2000],
+    pub vram: [u8; VRAM_SIZE],
 
     /// Keypad Representation; Conveys whether a key is pressed (true) or not pressed
     /// (false) currently.
@@ -69,18 +319,128 @@ pub struct Ferrous {
     /// Has the interpreter stopped? (through EXIT SCHIP)
     pub is_halted: bool,
 
-    /// If we should not increment I after Fx55, Fx65.
-    pub load_store_quirk: bool,
+    /// Stack error that halted the interpreter, if any.
+    #[cfg_attr(feature = "savestates", serde(default))]
+    pub stack_error: Option<StackError>,
+
+    /// How I is advanced after Fx55, Fx65.
+    #[cfg_attr(
+        feature = "savestates",
+        serde(
+            default,
+            alias = "load_store_quirk",
+            deserialize_with = "deserialize_load_store_mode"
+        )
+    )]
+    pub load_store_mode: LoadStoreMode,
 
     /// If we should ignore Vy in shift opcodes.
     pub shift_quirk: bool,
 
     /// Factor in the highest nibble of address to select register
-    // for jump.
+    /// for jump, like Super Chip. Set by the quirk profile.
     pub jump_quirk: bool,
 
+    /// If 8xy1, 8xy2 and 8xy3 reset VF, like the COSMAC VIP.
+    #[cfg_attr(feature = "savestates", serde(default))]
+    pub logic_quirk: bool,
+
+    /// Instructions the ROM expects to be executed per second, set by the
+    /// quirk profile. The interpreter doesn't pace itself, this is a hint
+    /// for frontends, see `cycles_per_tick`.
+    #[cfg_attr(
+        feature = "savestates",
+        serde(default = "default_instructions_per_second")
+    )]
+    pub instructions_per_second: u32,
+
+    /// If sprites drawn past the right edge wrap around to the left,
+    /// rather than being clipped.
+    #[cfg_attr(feature = "savestates", serde(default = "default_wrap"))]
+    pub wrap_x: bool,
+
+    /// If sprites drawn past the bottom edge wrap around to the top,
+    /// rather than being clipped.
+    #[cfg_attr(feature = "savestates", serde(default = "default_wrap"))]
+    pub wrap_y: bool,
+
+    /// If Fx0A should ignore keys that were already held when it started waiting.
+    #[cfg_attr(feature = "savestates", serde(default))]
+    pub key_release_quirk: bool,
+
+    /// What to do when an invalid or unknown opcode is encountered.
+    #[cfg_attr(feature = "savestates", serde(default))]
+    pub invalid_opcode_policy: InvalidOpcodePolicy,
+
+    /// If key presses should be queued until an instruction reads them.
+    #[cfg_attr(feature = "savestates", serde(default))]
+    pub input_queue: bool,
+
+    /// If delay timer wait loops should be skipped, see `set_skip_delays`.
+    #[cfg_attr(feature = "savestates", serde(default))]
+    pub skip_delays: bool,
+
+    /// Key presses that haven't been read yet, one bit per key.
+    #[cfg_attr(feature = "savestates", serde(default))]
+    key_queue: u16,
+
+    /// Keys held when the current Fx0A wait started, one bit per key.
+    /// Only used by the key release quirk.
+    #[cfg_attr(feature = "savestates", serde(default))]
+    wait_held_keys: Option<u16>,
+
+    /// State of the seeded random number generator used by Cxkk,
+    /// if seeded, see `set_rng_seed`.
+    #[cfg_attr(feature = "savestates", serde(default))]
+    rng_state: Option<u64>,
+
+    /// Recording replayed in lockstep mode, see `enter_lockstep`.
+    #[cfg_attr(feature = "savestates", serde(default))]
+    lockstep: Option<Lockstep>,
+
     /// Super Chip 8 flag registers.
     pub flag_reg: [u8; 8],
+
+    /// Length of the last loaded ROM in bytes.
+    #[cfg_attr(feature = "savestates", serde(default))]
+    rom_len: usize,
+
+    /// Checksum of the program region when the ROM was loaded,
+    /// if a ROM was loaded.
+    #[cfg_attr(feature = "savestates", serde(default))]
+    rom_checksum: Option<u64>,
+
+    /// Address ROMs are loaded at, and execution starts from.
+    #[cfg_attr(feature = "savestates", serde(default = "default_entry_point"))]
+    entry_point: usize,
+
+    /// Bytes of the last loaded ROM, for `reset_to_rom`.
+    #[cfg_attr(feature = "savestates", serde(default))]
+    rom: Vec<u8>,
+
+    /// Events that haven't been drained by the frontend yet.
+    #[cfg_attr(feature = "savestates", serde(skip))]
+    events: Vec<CpuEvent>,
+
+    /// Addresses `step_n` stops at.
+    #[cfg_attr(feature = "savestates", serde(skip))]
+    breakpoints: BTreeSet<usize>,
+
+    /// Address `step_n` stops at once, e.g. to run to the cursor.
+    #[cfg_attr(feature = "savestates", serde(skip))]
+    temporary_breakpoint: Option<usize>,
+
+    /// Are display changes recorded as draw events?
+    #[cfg_attr(feature = "savestates", serde(skip))]
+    record_draw_events: bool,
+
+    /// Draw events that haven't been drained by the frontend yet.
+    #[cfg_attr(feature = "savestates", serde(skip))]
+    draw_events: Vec<DrawEvent>,
+
+    /// Bounding box of the pixels changed since the last `take_dirty_region`.
+    #[cfg_attr(feature = "savestates", serde(skip))]
+    dirty_region: Option<(usize, usize, usize, usize)>,
 }
 
 impl Default for Ferrous {
@@ -89,6 +449,46 @@ impl Default for Ferrous {
     }
 }
 
+/// Create a new interpreter with the given ROM loaded.
+///
+/// # Example
+///
+/// ```rust
+/// use core::convert::TryFrom;
+/// use ferrous::CPU;
+///
+/// // 0x200: LD V0, 0x2A
+/// let cpu = CPU::try_from(&[0x60, 0x2A][..]).unwrap();
+///
+/// assert_eq!(cpu.fetch_opcode(), 0x602A);
+/// ```
+impl TryFrom<&[u8]> for Ferrous {
+    type Error = &'static str;
+
+    fn try_from(rom: &[u8]) -> Result<Self, Self::Error> {
+        let mut cpu = Self::new();
+        cpu.load_rom(rom)?;
+
+        Ok(cpu)
+    }
+}
+
+/// Access the general purpose registers by index.
+/// The index is masked to its lowest nibble, so `cpu[0x1F]` is VF.
+impl Index<u8> for Ferrous {
+    type Output = u8;
+
+    fn index(&self, index: u8) -> &Self::Output {
+        &self.reg[(index & 0xF) as usize]
+    }
+}
+
+impl IndexMut<u8> for Ferrous {
+    fn index_mut(&mut self, index: u8) -> &mut Self::Output {
+        &mut self.reg[(index & 0xF) as usize]
+    }
+}
+
 /// General Methods
 impl Ferrous {
     /// Create a new `CPU` instance.
@@ -96,7 +496,7 @@ impl Ferrous {
     /// # Example
     ///
     /// ```rust
-    /// use ch8_core::CPU;
+    /// use ferrous::CPU;
     ///
     /// let mut cpu = CPU::new();
     /// ```
@@ -116,24 +516,60 @@ impl Ferrous {
             id: 0,
             dt: 0,
             st: 0,
-            vram: [0; 0x2000],
+            beep_latch: false,
+            vram: [0; VRAM_SIZE],
             keypad: [false; 0x10],
             flag_reg: [0; 8],
             is_halted: false,
+            stack_error: None,
             is_highres: false,
-            load_store_quirk: false,
+            load_store_mode: LoadStoreMode::IncrementByXPlus1,
             shift_quirk: false,
             jump_quirk: false,
+            logic_quirk: false,
+            instructions_per_second: default_instructions_per_second(),
+            wrap_x: true,
+            wrap_y: true,
+            key_release_quirk: false,
+            invalid_opcode_policy: InvalidOpcodePolicy::Ignore,
+            input_queue: false,
+            skip_delays: false,
+            key_queue: 0,
+            wait_held_keys: None,
+            rng_state: None,
+            lockstep: None,
+            rom_len: 0,
+            rom_checksum: None,
+            entry_point: 0x200,
+            rom: Vec::new(),
+            events: Vec::new(),
+            record_draw_events: false,
+            draw_events: Vec::new(),
+            dirty_region: None,
+            breakpoints: BTreeSet::new(),
+            temporary_breakpoint: None,
+        }
+    }
+
+    /// Create a new `CPU` instance with the given memory image,
+    /// replacing the font sprites and everything else in memory.
+    ///
+    /// Unlike `load_rom`, the image is not offset to 0x200.
+    pub fn with_memory(memory: &[u8; 0x1000]) -> Self {
+        Self {
+            ram: *memory,
+            ..Self::new()
         }
     }
 
     /// Reset the interpreter to its initial state.
-    /// Flag Registers & Quirks are persistent even after a reset.
+    /// Flag Registers, Quirks, the ROM length & the entry point
+    /// are persistent even after a reset.
     ///
     /// # Example
     ///
     /// ```rust
-    /// use ch8_core::CPU;
+    /// use ferrous::CPU;
     ///
     /// let mut cpu = CPU::new();
     ///
@@ -142,32 +578,129 @@ impl Ferrous {
     /// cpu.reset();
     /// ```
     pub fn reset(&mut self) {
+        let was_beeping = self.should_beep();
+
         // Clear only the non-reserved memory.
         self.ram[0x200..].fill(0);
         self.reg.fill(0);
 
-        self.pc = 0x200;
+        self.pc = self.entry_point;
         self.sp = 0;
         self.id = 0;
         self.dt = 0;
         self.st = 0;
+        self.beep_latch = false;
 
         self.vram.fill(0);
         self.keypad.fill(false);
+        self.key_queue = 0;
+        self.wait_held_keys = None;
 
         self.is_halted = false;
+        self.stack_error = None;
         self.is_highres = false;
+
+        self.events.clear();
+        self.emit_sound_change(was_beeping);
+
+        // Spectators start over from a blank, low resolution display.
+        self.mark_screen_dirty();
+        self.draw_events.clear();
+        self.emit_draw(DrawEvent::Resolution { is_highres: false });
+        self.emit_draw(DrawEvent::Clear);
+    }
+
+    /// Reset the interpreter, and load the last loaded ROM again,
+    /// restoring any of its bytes the program has modified.
+    ///
+    /// Returns an `Err`, if the ROM no longer fits in the memory after
+    /// the entry point, e.g. after restoring an inconsistent state.
+    pub fn reset_to_rom(&mut self) -> Result<(), &'static str> {
+        let rom = self.rom.clone();
+
+        self.reset();
+        self.load_rom(&rom)
+    }
+
+    /// Set the address ROMs are loaded at and execution starts from,
+    /// for machines like the ETI-660 that start programs at 0x600.
+    /// The program counter is moved there, as if the interpreter was reset.
+    ///
+    /// Moving the entry point forgets the last loaded ROM, as it was
+    /// loaded at the previous address, so load it again afterwards.
+    ///
+    /// Returns an `Err`, if the address is below 0x200 or outside of memory.
+    pub fn set_entry_point(&mut self, address: usize) -> Result<(), &'static str> {
+        if !(0x200..self.ram.len()).contains(&address) {
+            return Err("Entry point is outside of the program memory.");
+        }
+
+        if address != self.entry_point {
+            self.rom_len = 0;
+            self.rom.clear();
+            self.rom_checksum = None;
+        }
+
+        self.entry_point = address;
+        self.pc = address;
+
+        Ok(())
+    }
+
+    /// Get the address ROMs are loaded at and execution starts from.
+    pub fn entry_point(&self) -> usize {
+        self.entry_point
+    }
+
+    /// Set all the general purpose registers, V0 to VF, at once.
+    pub fn set_registers(&mut self, values: &[u8; 16]) {
+        self.reg = *values;
+    }
+
+    /// Set the index register.
+    ///
+    /// Returns an `Err`, if the address is outside of memory.
+    pub fn set_index(&mut self, address: usize) -> Result<(), &'static str> {
+        if address >= self.ram.len() {
+            return Err("Index is outside of memory.");
+        }
+
+        self.id = address;
+
+        Ok(())
+    }
+
+    /// Set the program counter.
+    ///
+    /// Returns an `Err`, if an opcode can't be fetched from the address.
+    pub fn set_pc(&mut self, address: usize) -> Result<(), &'static str> {
+        if address + 1 >= self.ram.len() {
+            return Err("Program counter is outside of memory.");
+        }
+
+        self.pc = address;
+
+        Ok(())
+    }
+
+    /// Set the entry point to the given address, and load the ROM there.
+    ///
+    /// Returns an `Err`, if the address isn't a valid entry point,
+    /// or the ROM doesn't fit in the memory after it.
+    pub fn load_rom_at(&mut self, address: usize, buffer: &[u8]) -> Result<(), &'static str> {
+        self.set_entry_point(address)?;
+        self.load_rom(buffer)
     }
 
     /// Load a ROM into the working memory thus finalizing for execution.
     ///
-    /// Returns an `Err`, if the buffer's length is larger than the permitted,
-    /// 3584 bytes.
+    /// Returns an `Err`, if the buffer's length is larger than the memory
+    /// after the entry point, which is the permitted 3584 bytes by default.
     ///
     /// # Example
     ///
     /// ```rust
-    /// use ch8_core::CPU;
+    /// use ferrous::CPU;
     ///
     /// let mut cpu = CPU::new();
     ///
@@ -176,23 +709,153 @@ impl Ferrous {
     /// cpu.load_rom(&[0]);
     /// ```
     pub fn load_rom(&mut self, buffer: &[u8]) -> Result<(), &'static str> {
+        let start = self.entry_point;
+
         // Return an error, if bounds are exceeded.
-        if buffer.len() > 3584 {
-            return Err("ROM\'s length is larger than the permitted 3584 bytes.");
+        if buffer.len() > self.ram.len() - start {
+            return Err("ROM\'s length is larger than the memory after the entry point.");
         }
 
         // Copy the ROM buffer.
-        self.ram[0x200..0x200 + buffer.len()].copy_from_slice(&buffer);
+        self.ram[start..start + buffer.len()].copy_from_slice(buffer);
+        self.rom_len = buffer.len();
+        self.rom = buffer.to_vec();
+        self.rom_checksum = Some(self.memory_checksum());
+
+        Ok(())
+    }
+
+    /// Copy the given bytes into memory, starting at the given address.
+    ///
+    /// Returns an `Err`, if the bytes don't fit in the 4 KB of memory.
+    pub fn set_memory_region(&mut self, start: usize, bytes: &[u8]) -> Result<(), &'static str> {
+        let region = start
+            .checked_add(bytes.len())
+            .and_then(|end| self.ram.get_mut(start..end))
+            .ok_or("Memory region is out of the bounds of memory.")?;
+
+        region.copy_from_slice(bytes);
 
         Ok(())
     }
 
+    /// Replace the loaded ROM with the given buffer, without resetting
+    /// the interpreter. Useful for hot reloading a ROM during development.
+    ///
+    /// The previous ROM's bytes are cleared, while the registers, timers,
+    /// stack and the rest of the memory are kept as they are.
+    ///
+    /// Returns an `Err`, if the buffer's length is larger than the memory
+    /// after the entry point.
+    pub fn reload_rom(&mut self, buffer: &[u8]) -> Result<(), &'static str> {
+        let start = self.entry_point;
+
+        if buffer.len() > self.ram.len() - start {
+            return Err("ROM\'s length is larger than the memory after the entry point.");
+        }
+
+        self.ram[start..start + self.rom_len].fill(0);
+
+        self.load_rom(buffer)
+    }
+
+    /// Detect the platform features the given ROM uses, without loading it.
+    ///
+    /// See `FeatureSet::detect` for the approximations this makes.
+    pub fn detect_feature_set(rom: &[u8]) -> FeatureSet {
+        FeatureSet::detect(rom)
+    }
+
+    /// Get the Super Chip flag registers.
+    pub fn flag_registers(&self) -> &[u8; 8] {
+        &self.flag_reg
+    }
+
+    /// Set the Super Chip flag registers, e.g. restoring
+    /// them from a previous session.
+    pub fn set_flag_registers(&mut self, flags: &[u8; 8]) {
+        self.flag_reg = *flags;
+    }
+
+    /// Get the length of the last loaded ROM in bytes.
+    pub fn rom_len(&self) -> usize {
+        self.rom_len
+    }
+
+    /// Get the program region, the memory the last ROM was loaded into.
+    ///
+    /// This reflects any changes the program made to its own code,
+    /// see `has_self_modified`.
+    pub fn rom_bytes(&self) -> &[u8] {
+        &self.ram[self.entry_point..self.entry_point + self.rom_len]
+    }
+
+    /// Count the instruction forms in the program region, see `opcode_histogram`.
+    pub fn opcode_histogram(&self) -> BTreeMap<&'static str, u32> {
+        opcode_histogram(
+            &self.ram[..self.entry_point + self.rom_len],
+            self.entry_point,
+        )
+    }
+
+    /// Get a checksum of the program region, the memory the last ROM was loaded into.
+    pub fn memory_checksum(&self) -> u64 {
+        fnv1a(self.rom_bytes())
+    }
+
+    /// Has the program region changed since the ROM was loaded?
+    ///
+    /// This flags ROMs that rewrite their own code, e.g. to
+    /// decompress or decrypt it. Always `false` before a ROM is loaded.
+    pub fn has_self_modified(&self) -> bool {
+        matches!(self.rom_checksum, Some(checksum) if checksum != self.memory_checksum())
+    }
+
+    /// Get what the memory at the given address is used for.
+    pub fn memory_region(&self, address: usize) -> MemoryRegion {
+        match address {
+            0x000..=0x04F => MemoryRegion::LowResFont,
+            0x050..=0x0EF => MemoryRegion::HighResFont,
+            _ if address < self.entry_point => MemoryRegion::Reserved,
+            _ if address < self.entry_point + self.rom_len => MemoryRegion::Program,
+            _ => MemoryRegion::Data,
+        }
+    }
+
+    /// Get the number of bytes of memory, after the loaded ROM,
+    /// that the program can use for its own purposes.
+    pub fn free_memory(&self) -> usize {
+        self.ram.len() - (self.entry_point + self.rom_len)
+    }
+
+    /// Get a reference to the given range of memory.
+    ///
+    /// Returns `None`, if the range is out of the bounds of memory.
+    pub fn memory_slice(&self, range: Range<usize>) -> Option<&[u8]> {
+        self.ram.get(range)
+    }
+
+    /// Get a mutable reference to the given range of memory.
+    ///
+    /// Returns `None`, if the range is out of the bounds of memory.
+    pub fn memory_slice_mut(&mut self, range: Range<usize>) -> Option<&mut [u8]> {
+        self.ram.get_mut(range)
+    }
+
     /// Decrement the delay timer and sound timer if they are non-zero.
     ///
     /// They are expected to be decremented at 60Hz therefore your frontend
     /// should call this function roughly every 16.67 milliseconds for an
     /// accurate emulation.
+    ///
+    /// Executing cycles doesn't step the timers, so a frontend that pauses
+    /// emulation by not calling this freezes them as well. The one exception
+    /// is a delay wait loop skipped under `set_skip_delays`, which zeroes the
+    /// delay timer at once.
     pub fn step_timers(&mut self) {
+        let was_beeping = self.should_beep();
+        self.beep_latch = self.st > 0;
+
         if self.dt > 0 {
             self.dt -= 1;
         }
@@ -200,6 +863,117 @@ impl Ferrous {
         if self.st > 0 {
             self.st -= 1;
         }
+
+        self.emit_sound_change(was_beeping);
+    }
+
+    /// Get the delay timer.
+    pub fn delay_timer(&self) -> u8 {
+        self.dt
+    }
+
+    /// Get the sound timer.
+    pub fn sound_timer(&self) -> u8 {
+        self.st
+    }
+
+    /// Set the delay timer, like Fx15.
+    pub fn set_delay_timer(&mut self, value: u8) {
+        self.dt = value;
+    }
+
+    /// Set the sound timer, like Fx18, emitting a sound event if the beep
+    /// starts or stops.
+    pub fn set_sound_timer(&mut self, value: u8) {
+        let was_beeping = self.should_beep();
+        self.st = value;
+
+        self.emit_sound_change(was_beeping);
+    }
+
+    /// Set both the delay timer and the sound timer.
+    pub fn set_timers(&mut self, delay: u8, sound: u8) {
+        self.set_delay_timer(delay);
+        self.set_sound_timer(sound);
+    }
+
+    /// Should the beep be playing?
+    ///
+    /// This is true while the sound timer is non-zero, and also for the
+    /// rest of the tick in which it reached zero, so that a sound timer of 1
+    /// set in between two calls to `step_timers` still produces a full
+    /// 1/60th of a second of beep.
+    ///
+    /// As the sound timer is frozen while `step_timers` isn't called,
+    /// this stays true while paused, frontends should silence the beep
+    /// themselves when emulation isn't running.
+    pub fn should_beep(&self) -> bool {
+        self.st > 0 || self.beep_latch
+    }
+
+    /// Take the events emitted since the last call.
+    ///
+    /// Frontends are expected to call this once per frame, only the
+    /// most recent events are kept if they don't.
+    pub fn drain_events(&mut self) -> Vec<CpuEvent> {
+        core::mem::take(&mut self.events)
+    }
+
+    /// Enable or disable recording changes to the display as draw events.
+    ///
+    /// Unlike `drain_events`, no draw events are dropped, as replaying
+    /// them needs all of them. Frontends that enable this are expected
+    /// to call `drain_draw_events` every frame.
+    pub fn set_record_draw_events(&mut self, value: bool) {
+        self.record_draw_events = value;
+
+        if !value {
+            self.draw_events.clear();
+        }
+    }
+
+    /// Get the bounding box of the pixels changed since the last call
+    /// to `take_dirty_region`, as inclusive `(x0, y0, x1, y1)` coordinates.
+    ///
+    /// Returns `None`, if the display hasn't changed.
+    pub fn dirty_region(&self) -> Option<(usize, usize, usize, usize)> {
+        self.dirty_region
+    }
+
+    /// Take the bounding box of the pixels changed since the last call,
+    /// so that a frontend only has to update that part of the screen.
+    ///
+    /// Clearing, scrolling or changing the resolution marks the whole
+    /// screen as changed. Writes to `vram` from outside aren't tracked.
+    pub fn take_dirty_region(&mut self) -> Option<(usize, usize, usize, usize)> {
+        self.dirty_region.take()
+    }
+
+    /// Take the draw events recorded since the last call.
+    pub fn drain_draw_events(&mut self) -> Vec<DrawEvent> {
+        core::mem::take(&mut self.draw_events)
+    }
+
+    /// Apply a draw event recorded by another interpreter to the display.
+    ///
+    /// Only the display and resolution are changed, collisions are not
+    /// reported in VF.
+    pub fn apply_draw_event(&mut self, event: &DrawEvent) {
+        match event {
+            DrawEvent::Clear => self.op_00e0(),
+
+            DrawEvent::Sprite {
+                x, y, bytes, wide, ..
+            } => {
+                self.draw_sprite(*x as usize, *y as usize, bytes, *wide);
+                self.emit_draw(event.clone());
+            }
+
+            DrawEvent::ScrollDown(n) => self.op_00cn(*n),
+            DrawEvent::ScrollRight => self.op_00fb(),
+            DrawEvent::ScrollLeft => self.op_00fc(),
+            DrawEvent::Resolution { is_highres } => self.switch_resolution(*is_highres),
+        }
     }
 
     /// Reset the keypad to its initial state.
@@ -208,6 +982,7 @@ impl Ferrous {
     /// by resetting all values in the keypad array to false.
     pub fn reset_keys(&mut self) {
         self.keypad.fill(false);
+        self.key_queue = 0;
     }
 
     /// Set the key to either be pressed (true) or unpressed (false)
@@ -216,19 +991,146 @@ impl Ferrous {
         self.keypad[index] = value;
     }
 
-    /// Set the load/store quirk to the given boolean value.
-    pub fn set_load_store(&mut self, value: bool) {
-        self.load_store_quirk = value;
+    /// Press and release several keys at once, one bit per key,
+    /// the bit at position k being key k.
+    ///
+    /// Keys in neither mask keep their state, keys in both are released.
+    /// This suits frontends that poll every key each frame, e.g. by passing
+    /// the held keys as `pressed` and the rest as `released`, as well as
+    /// those that handle press and release events one at a time.
+    pub fn apply_input_diff(&mut self, pressed: u16, released: u16) {
+        for (index, key) in self.keypad.iter_mut().enumerate() {
+            if released & 1 << index != 0 {
+                *key = false;
+            } else if pressed & 1 << index != 0 {
+                *key = true;
+            }
+        }
+    }
+
+    /// Check if the given key is pressed.
+    ///
+    /// The key is masked to its lowest nibble, as the keypad only has 16 keys.
+    pub fn key_is_down(&self, key: u8) -> bool {
+        self.keypad[(key & 0xF) as usize]
+    }
+
+    /// Queue a press of the key at the given index, so that it is seen
+    /// by the next `Ex9E`, `ExA1` or `Fx0A` even if the key is released
+    /// before that instruction is executed.
+    ///
+    /// Does nothing unless the input queue is enabled.
+    pub fn queue_key_press(&mut self, index: u8) {
+        if self.input_queue {
+            self.key_queue |= 1 << (index & 0xF);
+        }
+    }
+
+    /// Enable or disable skipping of delay timer wait loops.
+    ///
+    /// When enabled, a `Fx07` immediately followed by `SE Vx, 0` and a jump
+    /// back to the `Fx07` is treated as a busy wait, and the delay timer is
+    /// zeroed so that the wait finishes at once. This is a heuristic; waits
+    /// written differently are not detected, and ROMs that do other work
+    /// while the timer runs down can behave differently. The timer is not
+    /// stepped, so the skipped time isn't accounted for anywhere else.
+    pub fn set_skip_delays(&mut self, value: bool) {
+        self.skip_delays = value;
+    }
+
+    /// Enable or disable the input queue.
+    pub fn set_input_queue(&mut self, value: bool) {
+        self.input_queue = value;
+
+        if !value {
+            self.key_queue = 0;
+        }
+    }
+
+    /// Set the load/store quirk to the given boolean value.
+    /// When enabled I is left unchanged, otherwise it is incremented by X + 1.
+    pub fn set_load_store(&mut self, value: bool) {
+        self.load_store_mode = load_store_mode_from_quirk(value);
+    }
+
+    /// Set how I is advanced after Fx55, Fx65.
+    pub fn set_load_store_mode(&mut self, mode: LoadStoreMode) {
+        self.load_store_mode = mode;
+    }
+
+    /// Set the shift quirk to the given boolean value.
+    pub fn set_shift(&mut self, value: bool) {
+        self.shift_quirk = value;
+    }
+
+    /// Set the jump quirk to the given value.
+    pub fn set_jump(&mut self, value: bool) {
+        self.jump_quirk = value;
+    }
+
+    /// Set the logic quirk to the given value.
+    /// When enabled, 8xy1, 8xy2 and 8xy3 reset VF after the operation.
+    pub fn set_logic(&mut self, value: bool) {
+        self.logic_quirk = value;
+    }
+
+    /// Set whether sprites wrap around the horizontal and vertical edges
+    /// of the screen, or are clipped by them. Both wrap by default.
+    ///
+    /// Only the pixels past the edges are affected, the sprite's
+    /// position always wraps around the screen.
+    pub fn set_wrap(&mut self, x: bool, y: bool) {
+        self.wrap_x = x;
+        self.wrap_y = y;
+    }
+
+    /// Set the key release quirk to the given value.
+    /// When enabled, Fx0A only accepts a key pressed after it started waiting,
+    /// so a key held down from an earlier wait doesn't end the next one at once.
+    pub fn set_key_release(&mut self, value: bool) {
+        self.key_release_quirk = value;
+        self.wait_held_keys = None;
+    }
+
+    /// Switch between low and high resolution mode, clearing the screen as
+    /// 00FE and 00FF do, e.g. to force a mode for a ROM that doesn't set it.
+    /// Unlike writing `is_highres` directly, the frontend is notified of the switch.
+    pub fn set_high_res(&mut self, value: bool) {
+        self.switch_resolution(value);
+        self.op_00e0();
+    }
+
+    /// Set what the interpreter does when it encounters
+    /// an invalid or unknown opcode.
+    pub fn set_invalid_opcode_policy(&mut self, policy: InvalidOpcodePolicy) {
+        self.invalid_opcode_policy = policy;
     }
 
-    /// Set the shift quirk to the given boolean value.
-    pub fn set_shift(&mut self, value: bool) {
-        self.shift_quirk = value;
+    /// Set all the quirks to match the behaviour of the given platform.
+    pub fn apply_profile(&mut self, profile: QuirkProfile) {
+        let (load_store, shift, jump, logic) = match profile {
+            QuirkProfile::Cosmac => (LoadStoreMode::IncrementByXPlus1, false, false, true),
+            QuirkProfile::XoChip => (LoadStoreMode::IncrementByXPlus1, false, false, false),
+            QuirkProfile::SchipModern => (LoadStoreMode::Unchanged, true, true, false),
+            QuirkProfile::SchipLegacy => (LoadStoreMode::IncrementByX, true, true, false),
+        };
+
+        self.load_store_mode = load_store;
+        self.shift_quirk = shift;
+        self.jump_quirk = jump;
+        self.logic_quirk = logic;
+        self.instructions_per_second = profile.instructions_per_second();
     }
 
-    /// Set the jump quirk to the given value.
-    pub fn set_jump(&mut self, value: bool) {
-        self.jump_quirk = value;
+    /// Set the instructions the ROM expects to be executed per second.
+    pub fn set_instructions_per_second(&mut self, value: u32) {
+        self.instructions_per_second = value;
+    }
+
+    /// Get the cycles to execute per 60 Hz tick, at the
+    /// instructions per second the ROM expects.
+    pub fn cycles_per_tick(&self) -> u16 {
+        (self.instructions_per_second / TICK_RATE).clamp(1, u16::MAX as u32) as u16
     }
 
     /// Execute one fetch-decode-execute cycle,
@@ -236,6 +1138,12 @@ impl Ferrous {
     ///
     /// Currently if the CPU is halted as a result of a superchip
     /// HALT opcode this function does nothing but return None.
+    ///
+    /// Invalid or unknown opcodes also return None, after being
+    /// handled according to the invalid opcode policy.
+    ///
+    /// A stack overflow or underflow halts the interpreter, records
+    /// the error in `stack_error` and returns None.
     pub fn execute_cycle(&mut self) -> Option<u16> {
         if self.is_halted {
             return None;
@@ -270,7 +1178,12 @@ impl Ferrous {
             // 0x0000 - 0x1000
             (0x0, 0x0, 0xC, _) => self.op_00cn(nibbles.3),
             (0x0, 0x0, 0xE, 0x0) => self.op_00e0(),
-            (0x0, 0x0, 0xE, 0xE) => self.op_00ee(),
+            (0x0, 0x0, 0xE, 0xE) => {
+                if let Err(error) = self.op_00ee() {
+                    self.raise_stack_error(error);
+                    return None;
+                }
+            }
             (0x0, 0x0, 0xF, 0xB) => self.op_00fb(),
             (0x0, 0x0, 0xF, 0xC) => self.op_00fc(),
             (0x0, 0x0, 0xF, 0xD) => self.op_00fd(),
@@ -279,7 +1192,12 @@ impl Ferrous {
 
             // 0x1000 - 0x8000
             (0x1, _, _, _) => self.op_1nnn(nnn),
-            (0x2, _, _, _) => self.op_2nnn(nnn),
+            (0x2, _, _, _) => {
+                if let Err(error) = self.op_2nnn(nnn) {
+                    self.raise_stack_error(error);
+                    return None;
+                }
+            }
             (0x3, _, _, _) => self.op_3xkk(x, kk),
             (0x4, _, _, _) => self.op_4xkk(x, kk),
             (0x5, _, _, 0) => self.op_5xy0(x, y),
@@ -329,30 +1247,565 @@ impl Ferrous {
             (0xF, _, 0x8, 0x5) => self.op_fx85(x),
 
             // Unknown/Invalid opcodes
-            _ => return None,
+            _ => {
+                match self.invalid_opcode_policy {
+                    InvalidOpcodePolicy::Ignore => {}
+                    InvalidOpcodePolicy::Halt => self.halt(),
+                    InvalidOpcodePolicy::Panic => panic!(
+                        "Invalid or unknown opcode {:#06X} at {:#05X}.",
+                        opcode,
+                        self.pc - 2
+                    ),
+                }
+
+                return None;
+            }
         }
 
         Some(opcode)
     }
 
+    /// Execute one cycle like `execute_cycle`, and describe everything it
+    /// changed, e.g. for a debugger explaining what an instruction did.
+    ///
+    /// The state is copied before the cycle and compared after it, memory
+    /// and the screen included, so this is far slower than `execute_cycle`
+    /// and is meant for stepping by hand.
+    pub fn execute_cycle_traced(&mut self) -> CycleDelta {
+        let (pc, is_halted) = (self.pc, self.is_halted);
+        let (reg, id, sp, dt, st) = (self.reg, self.id, self.sp, self.dt, self.st);
+        let (stack, flag_reg, is_highres) = (self.stack, self.flag_reg, self.is_highres);
+        let (ram, vram) = (self.ram, self.vram);
+
+        let opcode = self.execute_cycle();
+
+        CycleDelta {
+            opcode,
+            pc: (pc, self.pc),
+            registers: changes(&reg, &self.reg),
+            index: changed(id, self.id),
+            stack_pointer: changed(sp, self.sp),
+            stack: changes(&stack, &self.stack),
+            delay_timer: changed(dt, self.dt),
+            sound_timer: changed(st, self.st),
+            memory: changes(&ram, &self.ram),
+            flag_registers: changes(&flag_reg, &self.flag_reg),
+            pixels: changes(&vram, &self.vram)
+                .into_iter()
+                .map(|(index, ..)| index)
+                .collect(),
+            highres: changed(is_highres, self.is_highres),
+            halted: !is_halted && self.is_halted,
+        }
+    }
+
+    /// Execute up to `n` cycles, stopping early if the interpreter halts,
+    /// reaches a breakpoint or encounters an invalid or unknown opcode.
+    ///
+    /// A breakpoint at the current address doesn't stop the first cycle,
+    /// so that execution can be resumed from it.
+    pub fn step_n(&mut self, n: usize) -> StepSummary {
+        let mut summary = StepSummary {
+            executed: 0,
+            stopped: None,
+        };
+
+        while summary.executed < n {
+            if self.is_halted {
+                summary.stopped = Some(StopReason::Halted);
+                break;
+            }
+
+            if summary.executed > 0 && self.is_breakpoint(self.pc) {
+                if self.temporary_breakpoint == Some(self.pc) {
+                    self.temporary_breakpoint = None;
+                }
+
+                summary.stopped = Some(StopReason::Breakpoint(self.pc));
+                break;
+            }
+
+            let opcode = self.fetch_opcode();
+            let result = self.execute_cycle();
+
+            summary.executed += 1;
+
+            if result.is_none() {
+                summary.stopped = Some(if self.is_halted {
+                    StopReason::Halted
+                } else {
+                    StopReason::InvalidOpcode(opcode)
+                });
+
+                break;
+            }
+        }
+
+        summary
+    }
+
+    /// Make `step_n` stop when the program counter reaches the given address.
+    pub fn add_breakpoint(&mut self, address: usize) {
+        self.breakpoints.insert(address);
+    }
+
+    /// Remove a breakpoint added with `add_breakpoint`.
+    pub fn remove_breakpoint(&mut self, address: usize) {
+        self.breakpoints.remove(&address);
+    }
+
+    /// Make `step_n` stop the next time the program counter reaches the
+    /// given address, after which the breakpoint is removed. This replaces
+    /// the previous temporary breakpoint, if there is one.
+    pub fn set_temporary_breakpoint(&mut self, address: usize) {
+        self.temporary_breakpoint = Some(address);
+    }
+
+    /// Remove the temporary breakpoint, if it wasn't hit yet.
+    pub fn clear_temporary_breakpoint(&mut self) {
+        self.temporary_breakpoint = None;
+    }
+
+    /// Get the address of the temporary breakpoint, if it wasn't hit yet.
+    pub fn temporary_breakpoint(&self) -> Option<usize> {
+        self.temporary_breakpoint
+    }
+
+    /// Check if there is a breakpoint, or the temporary breakpoint, at the given address.
+    pub fn is_breakpoint(&self, address: usize) -> bool {
+        self.breakpoints.contains(&address) || self.temporary_breakpoint == Some(address)
+    }
+
+    /// Iterate over the breakpoint addresses, in ascending order.
+    pub fn breakpoints(&self) -> impl Iterator<Item = usize> + '_ {
+        self.breakpoints.iter().copied()
+    }
+
+    /// Execute a full frame's worth of cycles, and then step the timers once.
+    ///
+    /// Returns the number of invalid or unknown opcodes encountered,
+    /// cycles skipped because the CPU is halted are not counted.
+    pub fn advance_frame(&mut self, cycles: u16) -> usize {
+        let mut invalid = 0;
+
+        for _ in 0..cycles {
+            if self.execute_cycle().is_none() && !self.is_halted {
+                invalid += 1;
+            }
+        }
+
+        self.step_timers();
+
+        invalid
+    }
+
+    /// Seed the random number generator used by Cxkk, so the same seed
+    /// always produces the same sequence of random bytes.
+    ///
+    /// `None` goes back to unpredictable random bytes.
+    pub fn set_rng_seed(&mut self, seed: Option<u64>) {
+        // Xorshift never leaves a zero state, so nudge it.
+        self.rng_state = seed.map(|seed| (seed ^ 0x9E37_79B9_7F4A_7C15).max(1));
+    }
+
+    /// Get the next random byte for Cxkk.
+    fn random_byte(&mut self) -> u8 {
+        match &mut self.rng_state {
+            // Xorshift64*, taking the highest byte of the output.
+            Some(state) => {
+                *state ^= *state >> 12;
+                *state ^= *state << 25;
+                *state ^= *state >> 27;
+
+                (state.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 56) as u8
+            }
+
+            None => rand::random(),
+        }
+    }
+
+    /// Enter lockstep mode, for fully reproducible runs.
+    ///
+    /// The random number generator is seeded, and every
+    /// `step_lockstep` then replays the recording's keys for the next
+    /// frame, with its fixed number of cycles. The same ROM, seed and
+    /// recording always produce identical execution.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ferrous::{InputRecording, CPU};
+    ///
+    /// let mut recording = InputRecording::new(10);
+    /// recording.record(0, 0b10);
+    ///
+    /// let mut cpu = CPU::new();
+    /// cpu.enter_lockstep(42, recording);
+    /// cpu.step_lockstep();
+    ///
+    /// assert!(cpu.key_is_down(1));
+    /// ```
+    pub fn enter_lockstep(&mut self, seed: u64, recording: InputRecording) {
+        self.set_rng_seed(Some(seed));
+        self.reset_keys();
+
+        self.lockstep = Some(Lockstep {
+            recording,
+            frame: 0,
+        });
+    }
+
+    /// Leave lockstep mode, returning the recording that was replayed.
+    ///
+    /// The random number generator stays seeded.
+    pub fn exit_lockstep(&mut self) -> Option<InputRecording> {
+        self.lockstep.take().map(|lockstep| lockstep.recording)
+    }
+
+    /// Is the interpreter in lockstep mode?
+    pub fn is_lockstep(&self) -> bool {
+        self.lockstep.is_some()
+    }
+
+    /// Replay the next frame of lockstep mode, see `advance_frame`.
+    ///
+    /// Returns the number of invalid or unknown opcodes encountered,
+    /// or `None` outside of lockstep mode.
+    pub fn step_lockstep(&mut self) -> Option<usize> {
+        let lockstep = self.lockstep.as_mut()?;
+        let keys = lockstep.recording.keys_at(lockstep.frame);
+        let cycles = lockstep.recording.cycles_per_frame;
+
+        lockstep.frame += 1;
+
+        for (index, key) in self.keypad.iter_mut().enumerate() {
+            *key = keys & 1 << index != 0;
+        }
+
+        Some(self.advance_frame(cycles))
+    }
+
+    /// Get a digest of the displayed pixels and the resolution,
+    /// to cheaply compare the display of two runs.
+    ///
+    /// The digest is stable between versions and platforms.
+    pub fn vram_digest(&self) -> u64 {
+        let pixels = &self.vram[..self.resolution().pixels()];
+
+        fnv1a([self.is_highres as u8].iter().chain(pixels))
+    }
+
+    /// Execute the given trace, checking that each instruction
+    /// is fetched from the same address as in the trace.
+    ///
+    /// The timers are stepped after every `cycles` instructions,
+    /// so this should match the reference implementation's setting.
+    /// A `cycles` of 0 never steps them.
+    ///
+    /// Returns the first instruction that diverged, if any.
+    pub fn compare_trace(&mut self, trace: &[TraceEntry], cycles: u16) -> Result<(), Divergence> {
+        for (cycle, &expected) in trace.iter().enumerate() {
+            if cycles > 0 && cycle > 0 && cycle % cycles as usize == 0 {
+                self.step_timers();
+            }
+
+            let actual = match self.memory_slice(self.pc..self.pc + 2) {
+                Some(&[high, low]) if !self.is_halted => Some(TraceEntry {
+                    pc: self.pc,
+                    opcode: u16::from_be_bytes([high, low]),
+                }),
+                _ => None,
+            };
+
+            if actual != Some(expected) {
+                return Err(Divergence {
+                    cycle,
+                    expected,
+                    actual,
+                });
+            }
+
+            self.execute_cycle();
+        }
+
+        Ok(())
+    }
+
     /// Fetch the VRAM as a reference to a u8 slice.
     pub fn get_video_buffer(&self) -> &[u8] {
         self.vram.as_ref()
     }
 
-    /// Get the current number of rows and columns as tuple.
-    pub fn get_height_width(&self) -> (usize, usize) {
+    /// Copy just the active screen and its resolution, e.g. to hand
+    /// a frame to a renderer on another thread.
+    ///
+    /// This is far cheaper than cloning the whole interpreter.
+    pub fn clone_display_only(&self) -> DisplaySnapshot {
+        let resolution = self.resolution();
+
+        DisplaySnapshot {
+            resolution,
+            pixels: self.vram[..resolution.pixels()].to_vec(),
+        }
+    }
+
+    /// Get the bytes Dxyn reads from I for the given height.
+    ///
+    /// A height of 0 reads the 32 bytes of a Super Chip 16x16 sprite,
+    /// two bytes per row. The bytes are cut short at the end of memory.
+    pub fn read_sprite(&self, height: usize) -> Vec<u8> {
+        self.sprite_bytes(height).to_vec()
+    }
+
+    /// Borrow the bytes Dxyn reads from I, see `read_sprite`.
+    fn sprite_bytes(&self, height: usize) -> &[u8] {
+        let length = if height == 0 { 32 } else { height };
+        let start = self.id.min(self.ram.len());
+        let end = start.saturating_add(length).min(self.ram.len());
+
+        &self.ram[start..end]
+    }
+
+    /// Iterate over the rows of the active screen, each row
+    /// being a slice as wide as the current resolution.
+    pub fn vram_rows(&self) -> impl Iterator<Item = &[u8]> {
+        let resolution = self.resolution();
+
+        self.vram[..resolution.pixels()].chunks_exact(resolution.width)
+    }
+
+    /// Get the active portion of the VRAM packed as 1 bit per pixel,
+    /// with the leftmost pixel in the most significant bit of each byte.
+    ///
+    /// This is 256 bytes in low resolution mode, and 1024 bytes in
+    /// high resolution mode.
+    pub fn packed_framebuffer(&self) -> Vec<u8> {
+        self.vram[..self.resolution().pixels()]
+            .chunks_exact(8)
+            .map(|pixels| {
+                pixels
+                    .iter()
+                    .fold(0, |byte, &pixel| (byte << 1) | (pixel & 1))
+            })
+            .collect()
+    }
+
+    /// Get the resolution of the current display mode.
+    pub fn resolution(&self) -> Resolution {
         if self.is_highres {
-            (64, 128)
+            Resolution::HIGH
         } else {
-            (32, 64)
+            Resolution::LOW
+        }
+    }
+
+    /// Get the current number of rows and columns as tuple.
+    pub fn get_height_width(&self) -> (usize, usize) {
+        let resolution = self.resolution();
+
+        (resolution.height, resolution.width)
+    }
+
+    /// Halt the interpreter as a result of the given stack error.
+    fn raise_stack_error(&mut self, error: StackError) {
+        // Undo the PC increment, so that it points at the faulting opcode.
+        self.pc -= 2;
+
+        self.stack_error = Some(error);
+        self.halt();
+    }
+
+    /// Halt the interpreter.
+    fn halt(&mut self) {
+        self.is_halted = true;
+        self.emit(CpuEvent::Halt);
+    }
+
+    /// Switch between low and high resolution mode.
+    fn switch_resolution(&mut self, value: bool) {
+        if self.is_highres != value {
+            self.is_highres = value;
+            self.emit(CpuEvent::ModeChange { is_highres: value });
+            self.mark_screen_dirty();
+            self.emit_draw(DrawEvent::Resolution { is_highres: value });
+        }
+    }
+
+    /// Emit a sound event, if the beep started or stopped.
+    fn emit_sound_change(&mut self, was_beeping: bool) {
+        match (was_beeping, self.should_beep()) {
+            (false, true) => self.emit(CpuEvent::SoundOn),
+            (true, false) => self.emit(CpuEvent::SoundOff),
+            _ => {}
+        }
+    }
+
+    /// Is the Fx07 that was just fetched the start of a loop
+    /// that waits for the delay timer to reach zero?
+    fn is_delay_wait_loop(&self, x: usize) -> bool {
+        let start = self.pc - 2;
+
+        let skip = 0x3000 | (x as u16) << 8;
+        let jump = 0x1000 | start as u16;
+
+        match self.ram.get(self.pc..self.pc + 4) {
+            Some(&[a, b, c, d]) => {
+                u16::from_be_bytes([a, b]) == skip && u16::from_be_bytes([c, d]) == jump
+            }
+
+            _ => false,
+        }
+    }
+
+    /// Grow the dirty region to include the given inclusive bounding box.
+    fn mark_dirty(&mut self, x0: usize, y0: usize, x1: usize, y1: usize) {
+        self.dirty_region = Some(match self.dirty_region {
+            Some((a0, b0, a1, b1)) => (a0.min(x0), b0.min(y0), a1.max(x1), b1.max(y1)),
+            None => (x0, y0, x1, y1),
+        });
+    }
+
+    /// Mark the whole screen of the current resolution as dirty.
+    fn mark_screen_dirty(&mut self) {
+        let resolution = self.resolution();
+
+        self.mark_dirty(0, 0, resolution.width - 1, resolution.height - 1);
+    }
+
+    /// Record a draw event, if recording is enabled.
+    fn emit_draw(&mut self, event: DrawEvent) {
+        if self.record_draw_events {
+            self.draw_events.push(event);
+        }
+    }
+
+    /// XOR the given sprite onto the display at (x, y), wrapping around
+    /// its edges, and return whether any lit pixels were erased.
+    fn draw_sprite(&mut self, x: usize, y: usize, bytes: &[u8], wide: bool) -> bool {
+        let resolution = self.resolution();
+        let (rows, cols) = (resolution.height, resolution.width);
+        let width = if wide { 2 } else { 1 };
+
+        let mut collision = false;
+
+        // Bounding box of the changed pixels, merged into the dirty region once.
+        let (mut x0, mut y0, mut x1, mut y1) = (cols, rows, 0, 0);
+
+        // Both dimensions are powers of two, so wrapping is a mask.
+        // The position always wraps, whether the sprite's pixels
+        // past the edges wrap or are clipped depends on the quirks.
+        let (x, y) = (x & (cols - 1), y & (rows - 1));
+
+        for (r, row) in bytes.chunks(width).enumerate() {
+            if y + r >= rows && !self.wrap_y {
+                break;
+            }
+
+            let py = (y + r) & (rows - 1);
+            let line = &mut self.vram[resolution.row(py)];
+
+            for (i, &byte) in row.iter().enumerate() {
+                if byte == 0 {
+                    continue;
+                }
+
+                if x + i * 8 >= cols && !self.wrap_x {
+                    break;
+                }
+
+                let px = (x + i * 8) & (cols - 1);
+
+                if px + 8 <= cols {
+                    // XOR all eight pixels at once, one byte per pixel.
+                    let pixels = &mut line[px..px + 8];
+                    let mut word = [0; 8];
+                    word.copy_from_slice(pixels);
+
+                    let old = u64::from_le_bytes(word);
+                    let mask = PIXEL_MASKS[byte as usize];
+
+                    collision |= old & mask != 0;
+                    pixels.copy_from_slice(&(old ^ mask).to_le_bytes());
+
+                    x0 = x0.min(px + byte.leading_zeros() as usize);
+                    x1 = x1.max(px + 7 - byte.trailing_zeros() as usize);
+                } else {
+                    // The byte straddles the right edge, wrap or clip each pixel.
+                    for c in 0..8 {
+                        if px + c >= cols && !self.wrap_x {
+                            break;
+                        }
+
+                        if byte & (0x80 >> c) != 0 {
+                            let px = (px + c) & (cols - 1);
+
+                            x0 = x0.min(px);
+                            x1 = x1.max(px);
+
+                            collision |= line[px] == 1;
+                            line[px] ^= 1;
+                        }
+                    }
+                }
+
+                y0 = y0.min(py);
+                y1 = y1.max(py);
+            }
+        }
+
+        if x0 <= x1 {
+            self.mark_dirty(x0, y0, x1, y1);
+        }
+
+        collision
+    }
+
+    /// Queue an event, dropping the oldest one if the queue is full.
+    fn emit(&mut self, event: CpuEvent) {
+        if self.events.len() == EVENT_QUEUE_LIMIT {
+            self.events.remove(0);
         }
+
+        self.events.push(event);
+    }
+
+    /// Check if the given key is pressed or queued,
+    /// consuming the queued press if any.
+    fn take_key(&mut self, key: u8) -> bool {
+        let bit = 1 << (key & 0xF);
+        let queued = self.key_queue & bit != 0;
+        self.key_queue &= !bit;
+
+        self.key_is_down(key) || queued
     }
 
     /// Fetch the next opcode that is to be executed from the ROM.
     pub fn fetch_opcode(&self) -> u16 {
         u16::from_be_bytes([self.ram[self.pc], self.ram[self.pc + 1]])
     }
+
+    /// Disassemble the instructions surrounding the program counter,
+    /// returning the address and mnemonic of each.
+    ///
+    /// Instructions are always decoded on even offsets from PC,
+    /// starting `before` instructions behind it and ending `after`
+    /// instructions ahead of it, clamped to the bounds of memory.
+    pub fn disasm_window(&self, before: usize, after: usize) -> Vec<(usize, String)> {
+        let start = self.pc - before.min(self.pc / 2) * 2;
+        let end = after
+            .saturating_mul(2)
+            .saturating_add(self.pc)
+            .min(self.ram.len() - 2);
+
+        (start..=end)
+            .step_by(2)
+            .map(|address| {
+                let opcode = u16::from_be_bytes([self.ram[address], self.ram[address + 1]]);
+
+                (address, disassemble(opcode))
+            })
+            .collect()
+    }
 }
 
 /// Standard CHIP opcodes.
@@ -361,13 +1814,21 @@ impl Ferrous {
     /// Clear the display.
     fn op_00e0(&mut self) {
         self.vram.fill(0);
+        self.mark_screen_dirty();
+        self.emit_draw(DrawEvent::Clear);
     }
 
     /// 00EE - RET  
     /// Return from a subroutine.
-    fn op_00ee(&mut self) {
+    fn op_00ee(&mut self) -> Result<(), StackError> {
+        if self.sp == 0 {
+            return Err(StackError::Underflow);
+        }
+
         self.sp -= 1;
         self.pc = self.stack[self.sp] as usize;
+
+        Ok(())
     }
 
     /// 1nnn - JP addr  
@@ -378,13 +1839,19 @@ impl Ferrous {
 
     /// 2nnn - CALL addr  
     /// Call subroutine at nnn.
-    fn op_2nnn(&mut self, nnn: u16) {
+    fn op_2nnn(&mut self, nnn: u16) -> Result<(), StackError> {
+        if self.sp == self.stack.len() {
+            return Err(StackError::Overflow);
+        }
+
         // Store return address.
         self.stack[self.sp] = self.pc as u16;
         self.sp += 1;
 
         // Call subroutine.
         self.pc = nnn as usize;
+
+        Ok(())
     }
 
     /// 3xkk - SE Vx, byte  
@@ -433,18 +1900,28 @@ impl Ferrous {
     /// Set Vx = Vx OR Vy.
     fn op_8xy1(&mut self, x: usize, y: usize) {
         self.reg[x] |= self.reg[y];
+        self.apply_logic_quirk();
     }
 
     /// 8xy2 - AND Vx, Vy  
     /// Set Vx = Vx AND Vy.
     fn op_8xy2(&mut self, x: usize, y: usize) {
         self.reg[x] &= self.reg[y];
+        self.apply_logic_quirk();
     }
 
     /// 8xy3 - XOR Vx, Vy  
     /// Set Vx = Vx XOR Vy.
     fn op_8xy3(&mut self, x: usize, y: usize) {
         self.reg[x] ^= self.reg[y];
+        self.apply_logic_quirk();
+    }
+
+    /// Reset VF after a logic instruction, under the logic quirk.
+    fn apply_logic_quirk(&mut self) {
+        if self.logic_quirk {
+            self.reg[0xF] = 0;
+        }
     }
 
     /// 8xy4 - ADD Vx, Vy  
@@ -510,6 +1987,10 @@ impl Ferrous {
 
     /// Bnnn - JP V0, addr  
     /// Jump to location nnn + V0.
+    ///
+    /// With the jump quirk this is Bxnn - JP Vx, addr, jumping to
+    /// nnn + Vx where x is the highest nibble of nnn, like Super Chip.
+    /// XO-Chip keeps the original behaviour.
     fn op_bnnn(&mut self, nnn: u16) {
         if self.jump_quirk {
             self.pc = nnn as usize + self.reg[(nnn >> 8) as usize & 0xF] as usize;
@@ -520,62 +2001,49 @@ impl Ferrous {
 
     /// Cxkk - RND Vx, byte  
     /// Set Vx = random byte AND kk.
+    ///
+    /// The random byte is reproducible once seeded, see `set_rng_seed`.
     fn op_cxkk(&mut self, x: usize, kk: u8) {
-        self.reg[x] = rand::random::<u8>() & kk;
+        self.reg[x] = self.random_byte() & kk;
     }
 
     /// Dxyn - DRW Vx, Vy, nibble  
     /// Display n-byte sprite starting at memory location I at (Vx, Vy),
     /// set VF = collision.
     fn op_dxyn(&mut self, vx: usize, vy: usize, n: usize) {
-        let (rows, cols) = self.get_height_width();
-
-        let x = self.reg[vx] as usize;
-        let y = self.reg[vy] as usize;
-
-        self.reg[0xF] = 0;
-
-        if n == 0 {
-            // Super Chip 16x16 sprite
-            for r in 0..16 {
-                for c in 0..16 {
-                    let byte = self.ram[self.id + (r * 2) + (if c > 7 { 1 } else { 0 })] as usize;
-
-                    if (byte & (0x80 >> (c % 8))) != 0 {
-                        let index = ((x + c) % cols) + ((y + r) % rows) * cols;
-
-                        if self.vram[index] == 1 {
-                            self.reg[0xF] = 1;
-                        }
-
-                        self.vram[index] ^= 1;
-                    }
-                }
-            }
-        } else {
-            // Chip 8xN sprite
-            for r in 0..n {
-                for c in 0..8 {
-                    let byte = self.ram[self.id + r] as usize;
-
-                    if (byte & (0x80 >> c)) != 0 {
-                        let index = ((x + c) % cols) + ((y + r) % rows) * cols;
-
-                        if self.vram[index] == 1 {
-                            self.reg[0xF] = 1;
-                        }
-
-                        self.vram[index] ^= 1;
-                    }
-                }
-            }
+        let x = self.reg[vx];
+        let y = self.reg[vy];
+
+        // A height of zero draws a Super Chip 16x16 sprite.
+        let wide = n == 0;
+
+        // Copy the sprite to the stack, as drawing borrows VRAM mutably.
+        let mut sprite = [0; 32];
+        let source = self.sprite_bytes(n);
+        let length = source.len();
+        sprite[..length].copy_from_slice(source);
+
+        let bytes = &sprite[..length];
+        let collision = self.draw_sprite(x as usize, y as usize, bytes, wide);
+
+        self.reg[0xF] = collision as u8;
+
+        // Avoid allocating the event's copy of the sprite, unless it is recorded.
+        if self.record_draw_events {
+            self.emit_draw(DrawEvent::Sprite {
+                x,
+                y,
+                bytes: bytes.to_vec(),
+                wide,
+                collision,
+            });
         }
     }
 
     /// Ex9E - SKP Vx  
     /// Skip next instruction if key with the value of Vx is pressed.
     fn op_ex9e(&mut self, x: usize) {
-        if self.keypad[self.reg[x] as usize] {
+        if self.take_key(self.reg[x]) {
             self.pc += 2;
         }
     }
@@ -583,7 +2051,7 @@ impl Ferrous {
     /// ExA1 - SKNP Vx  
     /// Skip next instruction if key with the value of Vx is not pressed.
     fn op_exa1(&mut self, x: usize) {
-        if !self.keypad[self.reg[x] as usize] {
+        if !self.take_key(self.reg[x]) {
             self.pc += 2;
         }
     }
@@ -591,17 +2059,49 @@ impl Ferrous {
     /// Fx07 - LD Vx, DT  
     /// Set Vx = delay timer value.
     fn op_fx07(&mut self, x: usize) {
+        if self.skip_delays && self.is_delay_wait_loop(x) {
+            self.dt = 0;
+        }
+
         self.reg[x] = self.dt;
     }
 
     /// Fx0A - LD Vx, K  
     /// Wait for a key press, store the value of the key in Vx.
+    ///
+    /// If several keys are pressed, the lowest one is stored.
+    /// Under the key release quirk, keys held when the wait started
+    /// are ignored until they are released and pressed again.
     fn op_fx0a(&mut self, x: usize) {
-        for (count, key) in self.keypad.iter_mut().enumerate() {
-            if *key {
-                self.reg[x] = count as u8;
-                return;
-            }
+        // Queued presses take precedence, lowest key first.
+        if self.key_queue != 0 {
+            let key = self.key_queue.trailing_zeros() as u8;
+
+            self.key_queue &= !(1 << key);
+            self.wait_held_keys = None;
+            self.reg[x] = key;
+            return;
+        }
+
+        let mut pressed = self
+            .keypad
+            .iter()
+            .enumerate()
+            .filter(|(_, &down)| down)
+            .fold(0u16, |keys, (index, _)| keys | 1 << index);
+
+        if self.key_release_quirk {
+            let held = self.wait_held_keys.get_or_insert(pressed);
+
+            // Released keys count as fresh presses once pressed again.
+            *held &= pressed;
+            pressed &= !*held;
+        }
+
+        if pressed != 0 {
+            self.wait_held_keys = None;
+            self.reg[x] = pressed.trailing_zeros() as u8;
+            return;
         }
 
         self.pc -= 2;
@@ -610,13 +2110,13 @@ impl Ferrous {
     /// Fx15 - LD DT, Vx  
     /// Set delay timer = Vx.
     fn op_fx15(&mut self, x: usize) {
-        self.dt = self.reg[x];
+        self.set_delay_timer(self.reg[x]);
     }
 
     /// Fx18 - LD ST, Vx  
     /// Set sound timer = Vx.
     fn op_fx18(&mut self, x: usize) {
-        self.st = self.reg[x];
+        self.set_sound_timer(self.reg[x]);
     }
 
     /// Fx1E - ADD I, Vx  
@@ -627,8 +2127,10 @@ impl Ferrous {
 
     /// Fx29 - LD F, Vx  
     /// Set I = location of sprite for digit Vx.
+    ///
+    /// Only hex digits have sprites, so Vx is masked to its lowest nibble.
     fn op_fx29(&mut self, x: usize) {
-        self.id = self.reg[x] as usize * 5;
+        self.id = (self.reg[x] & 0xF) as usize * 5;
     }
 
     /// Fx33 - LD B, Vx  
@@ -641,14 +2143,21 @@ impl Ferrous {
         self.ram[self.id + 2] = value % 10;
     }
 
+    /// Advance I after Fx55, Fx65 according to the load/store mode.
+    fn advance_load_store_index(&mut self, x: usize) {
+        self.id = match self.load_store_mode {
+            LoadStoreMode::IncrementByXPlus1 => (self.id + x + 1) & 0xFFFF,
+            LoadStoreMode::IncrementByX => (self.id + x) & 0xFFFF,
+            LoadStoreMode::Unchanged => self.id,
+        };
+    }
+
     /// Fx55 - LD [I], Vx  
     /// Store registers V0 through Vx in memory starting at location I.
     fn op_fx55(&mut self, x: usize) {
         self.ram[self.id..=self.id + x].copy_from_slice(&self.reg[0..=x]);
 
-        if !self.load_store_quirk {
-            self.id = (self.id + x + 1) & 0xFFFF;
-        }
+        self.advance_load_store_index(x);
     }
 
     /// Fx65 - LD Vx, [I]  
@@ -656,9 +2165,7 @@ impl Ferrous {
     fn op_fx65(&mut self, x: usize) {
         self.reg[0..=x].copy_from_slice(&self.ram[self.id..=self.id + x]);
 
-        if !self.load_store_quirk {
-            self.id = (self.id + x + 1) & 0xFFFF;
-        }
+        self.advance_load_store_index(x);
     }
 }
 
@@ -666,77 +2173,87 @@ impl Ferrous {
 impl Ferrous {
     /// 00Cn - SCD nibble  
     /// Scroll display N lines down.
+    ///
+    /// N is clamped to the screen height, as replayed draw events aren't
+    /// limited to a nibble. Scrolling by the full height clears the screen.
     fn op_00cn(&mut self, n: u8) {
-        let (rows, cols) = self.get_height_width();
+        let resolution = self.resolution();
+        let n = (n as usize).min(resolution.height);
 
-        // Get the number of rows that are retained.
-        let retained = rows as u8 - n;
-
-        // Get the index + 1 of the last pixel that is retained.
-        let last_index = cols as usize * retained as usize;
+        // Index of the first pixel that is scrolled off the screen.
+        let retained = resolution.index(0, resolution.height - n);
+        let shift = resolution.index(0, n);
 
         // Memove the retained pixels.
-        self.vram
-            .copy_within(0..last_index, cols as usize * n as usize);
+        self.vram.copy_within(0..retained, shift);
 
         // Clear the upper pixels.
-        self.vram[0..(cols as usize * n as usize)].fill(0);
+        self.vram[0..shift].fill(0);
+
+        self.mark_screen_dirty();
+        self.emit_draw(DrawEvent::ScrollDown(n as u8));
     }
 
     /// 00FB - SCR  
     /// Scroll display 4 pixels right.
     fn op_00fb(&mut self) {
-        let (rows, cols) = self.get_height_width();
+        let resolution = self.resolution();
+        let shift = SCROLL_PIXELS.min(resolution.width);
 
-        for row in 0..rows {
-            let start = cols as usize * row as usize;
+        for row in 0..resolution.height {
+            let Range { start, end } = resolution.row(row);
 
-            self.vram
-                .copy_within(start..(start + cols as usize - 4), start + 4);
+            self.vram.copy_within(start..(end - shift), start + shift);
 
-            self.vram[start..start + 4].fill(0);
+            self.vram[start..start + shift].fill(0);
         }
+
+        self.mark_screen_dirty();
+        self.emit_draw(DrawEvent::ScrollRight);
     }
 
     /// 00FC - SCL  
     /// Scroll display 4 pixels left.
     fn op_00fc(&mut self) {
-        let (rows, cols) = self.get_height_width();
+        let resolution = self.resolution();
+        let shift = SCROLL_PIXELS.min(resolution.width);
 
-        for row in 0..rows {
-            let start = cols as usize * row as usize;
-            let end = start + cols as usize;
+        for row in 0..resolution.height {
+            let Range { start, end } = resolution.row(row);
 
-            self.vram.copy_within((start + 4)..end, start);
+            self.vram.copy_within((start + shift)..end, start);
 
-            self.vram[(end - 4)..end].fill(0);
+            self.vram[(end - shift)..end].fill(0);
         }
+
+        self.mark_screen_dirty();
+        self.emit_draw(DrawEvent::ScrollLeft);
     }
 
     /// 00FD - EXIT  
     /// Exit CHIP interpreter.
     fn op_00fd(&mut self) {
-        self.is_halted = true;
+        self.halt();
     }
 
     /// 00FE - LOW  
     /// Disable extended screen mode.
     fn op_00fe(&mut self) {
-        self.is_highres = false;
-        self.op_00e0();
+        self.set_high_res(false);
     }
 
     /// 00FF - HIGH  
     /// Enable extended screen mode for full-screen graphics.
     fn op_00ff(&mut self) {
-        self.is_highres = true;
-        self.op_00e0();
+        self.set_high_res(true);
     }
 
     /// Fx30 - LD HF, Vx  
     /// Point I to 10-byte font sprite for VX (0..F)
+    ///
+    /// Only hex digits have sprites, so Vx is masked to its lowest nibble.
     fn op_fx30(&mut self, x: usize) {
-        self.id = (self.reg[x] as usize * 10) + 80;
+        self.id = ((self.reg[x] & 0xF) as usize * 10) + 80;
     }
 
     /// Fx75 - LD R, Vx  
@@ -751,3 +2268,6 @@ impl Ferrous {
         self.reg[0..=x].copy_from_slice(&self.flag_reg[0..=x]);
     }
 }
+
+#[cfg(test)]
+mod tests;
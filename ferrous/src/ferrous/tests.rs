@@ -0,0 +1,1401 @@
+use super::*;
+use crate::clock::{TickClock, TICK_TIME};
+use crate::delta::CycleDelta;
+use crate::disasm::{trace_disassemble, Category, Instruction, Listing, ListingItem};
+use alloc::string::ToString;
+use alloc::vec;
+
+mod arithmetic;
+mod drawing;
+mod opcodes;
+
+/// Create an interpreter with the given ROM loaded.
+fn cpu_with_rom(rom: &[u8]) -> Ferrous {
+    let mut cpu = Ferrous::new();
+    cpu.load_rom(rom).unwrap();
+
+    cpu
+}
+
+#[test]
+fn call_overflows_after_sixteen_levels() {
+    // 0x200: CALL 0x200
+    let mut cpu = cpu_with_rom(&[0x22, 0x00]);
+
+    for _ in 0..16 {
+        assert_eq!(cpu.execute_cycle(), Some(0x2200));
+    }
+
+    assert_eq!(cpu.sp, 16);
+    assert_eq!(cpu.execute_cycle(), None);
+    assert_eq!(cpu.stack_error, Some(StackError::Overflow));
+    assert!(cpu.is_halted);
+    assert_eq!(cpu.pc, 0x200);
+}
+
+#[test]
+fn return_underflows_on_empty_stack() {
+    // 0x200: RET
+    let mut cpu = cpu_with_rom(&[0x00, 0xEE]);
+
+    assert_eq!(cpu.execute_cycle(), None);
+    assert_eq!(cpu.stack_error, Some(StackError::Underflow));
+    assert!(cpu.is_halted);
+    assert_eq!(cpu.sp, 0);
+    assert_eq!(cpu.pc, 0x200);
+}
+
+#[test]
+fn interpreter_can_move_between_threads() {
+    fn assert_send<T: Send>() {}
+
+    assert_send::<Ferrous>();
+}
+
+#[test]
+fn clones_are_independent() {
+    // 0x200: LD F, V0
+    // 0x202: DRW V0, V0, 5
+    let mut cpu = cpu_with_rom(&[0xF0, 0x29, 0xD0, 0x05]);
+    cpu.execute_cycle().unwrap();
+    cpu.execute_cycle().unwrap();
+
+    let ram = cpu.ram;
+    let vram = cpu.vram;
+
+    let mut clone = cpu.clone();
+    clone.ram[0x200] = 0x00;
+    clone.vram.iter_mut().for_each(|pixel| *pixel ^= 1);
+    clone.reset();
+
+    assert_eq!(cpu.ram, ram);
+    assert_eq!(cpu.vram, vram);
+    assert_eq!(cpu.pc, 0x204);
+    assert!(cpu.vram.iter().any(|&pixel| pixel != 0));
+}
+
+#[test]
+fn flag_registers_survive_a_reset() {
+    // 0x200: LD R, V2
+    let mut cpu = cpu_with_rom(&[0xF2, 0x75]);
+    cpu.reg[..3].copy_from_slice(&[0x11, 0x22, 0x33]);
+    cpu.execute_cycle().unwrap();
+
+    cpu.reset();
+
+    // 0x200: LD V2, R
+    cpu.load_rom(&[0xF2, 0x85]).unwrap();
+    assert_eq!(cpu.reg[..3], [0; 3]);
+
+    cpu.execute_cycle().unwrap();
+    assert_eq!(cpu.reg[..3], [0x11, 0x22, 0x33]);
+}
+
+#[test]
+fn reset_clears_stack_error() {
+    let mut cpu = cpu_with_rom(&[0x00, 0xEE]);
+
+    cpu.execute_cycle();
+    cpu.reset();
+
+    assert_eq!(cpu.stack_error, None);
+    assert!(!cpu.is_halted);
+}
+
+#[test]
+fn packed_framebuffer_round_trips() {
+    let mut cpu = Ferrous::new();
+
+    for (i, pixel) in cpu.vram[..64 * 32].iter_mut().enumerate() {
+        *pixel = (i % 3 == 0) as u8;
+    }
+
+    let packed = cpu.packed_framebuffer();
+    assert_eq!(packed.len(), 256);
+    assert_eq!(packed[0], 0b1001_0010);
+
+    let unpacked: Vec<u8> = packed
+        .iter()
+        .flat_map(|byte| (0..8).rev().map(move |bit| (byte >> bit) & 1))
+        .collect();
+
+    assert_eq!(unpacked, &cpu.vram[..64 * 32]);
+}
+
+#[test]
+fn packed_framebuffer_respects_resolution() {
+    let mut cpu = Ferrous::new();
+    cpu.is_highres = true;
+
+    assert_eq!(cpu.packed_framebuffer().len(), 1024);
+}
+
+#[test]
+fn register_index_is_masked() {
+    let mut cpu = Ferrous::new();
+
+    cpu[0x1F] = 0xAB;
+
+    assert_eq!(cpu.reg[0xF], 0xAB);
+    assert_eq!(cpu[0xF], 0xAB);
+}
+
+#[test]
+fn skip_if_pressed_masks_key_index() {
+    // 0x200: SKP V0
+    let mut cpu = cpu_with_rom(&[0xE0, 0x9E]);
+
+    cpu[0x0] = 0xFF;
+    cpu.set_key_at_index(0xF, true);
+    cpu.execute_cycle();
+
+    assert_eq!(cpu.pc, 0x204);
+}
+
+#[test]
+fn keypad_skips_read_low_nibble_of_vx() {
+    // 0x200: SKP V0, 0x202: SKNP V0
+    let mut cpu = cpu_with_rom(&[0xE0, 0x9E, 0xE0, 0xA1]);
+
+    cpu[0x0] = 0x1A;
+    cpu.set_key_at_index(0xA, true);
+    cpu.execute_cycle();
+
+    assert_eq!(cpu.pc, 0x204);
+
+    cpu.pc = 0x202;
+    cpu.execute_cycle();
+
+    assert_eq!(cpu.pc, 0x204);
+}
+
+#[test]
+fn try_from_rejects_roms_larger_than_memory() {
+    let rom = vec![0; 0x1000 - 0x200 + 1];
+
+    assert!(Ferrous::try_from(&rom[..]).is_err());
+    assert!(Ferrous::try_from(&rom[1..]).is_ok());
+}
+
+#[test]
+fn machine_state_can_be_seeded() {
+    // 0x300: ADD V1, V2
+    let mut cpu = Ferrous::new();
+    cpu.memory_slice_mut(0x300..0x302)
+        .unwrap()
+        .copy_from_slice(&[0x81, 0x24]);
+
+    let mut registers = [0; 16];
+    registers[0x1] = 0x20;
+    registers[0x2] = 0x22;
+
+    cpu.set_registers(&registers);
+    cpu.set_index(0x400).unwrap();
+    cpu.set_pc(0x300).unwrap();
+    cpu.execute_cycle();
+
+    assert_eq!(cpu[0x1], 0x42);
+    assert_eq!(cpu.id, 0x400);
+    assert_eq!(cpu.pc, 0x302);
+}
+
+#[test]
+fn seeded_addresses_must_be_in_memory() {
+    let mut cpu = Ferrous::new();
+
+    assert!(cpu.set_index(0x1000).is_err());
+    assert!(cpu.set_pc(0xFFF).is_err());
+    assert!(cpu.set_pc(0xFFE).is_ok());
+    assert_eq!(cpu.id, 0);
+}
+
+#[test]
+fn key_is_down_masks_key() {
+    let mut cpu = Ferrous::new();
+
+    cpu.set_key_at_index(0xF, true);
+
+    assert!(cpu.key_is_down(0x1F));
+    assert!(cpu.key_is_down(0xFF));
+    assert!(!cpu.key_is_down(0x10));
+}
+
+#[test]
+fn wait_for_key_stores_lowest_pressed_key() {
+    // 0x200: LD V0, K
+    let mut cpu = cpu_with_rom(&[0xF0, 0x0A]);
+
+    cpu.set_key_at_index(0x5, true);
+    cpu.set_key_at_index(0x2, true);
+    cpu.execute_cycle();
+
+    assert_eq!(cpu[0x0], 0x2);
+    assert_eq!(cpu.pc, 0x202);
+}
+
+#[test]
+fn key_release_quirk_requires_a_fresh_press() {
+    // 0x200: LD V0, K
+    let mut cpu = cpu_with_rom(&[0xF0, 0x0A]);
+
+    cpu.set_key_release(true);
+    cpu.set_key_at_index(0x2, true);
+
+    // Held since before the wait started.
+    cpu.execute_cycle();
+    cpu.execute_cycle();
+
+    assert_eq!(cpu.pc, 0x200);
+
+    cpu.set_key_at_index(0x2, false);
+    cpu.execute_cycle();
+    cpu.set_key_at_index(0x2, true);
+    cpu.execute_cycle();
+
+    assert_eq!(cpu[0x0], 0x2);
+    assert_eq!(cpu.pc, 0x202);
+}
+
+#[test]
+fn wait_for_key_consumes_a_queued_press() {
+    // 0x200: LD V0, K
+    // 0x202: LD V1, K
+    let mut cpu = cpu_with_rom(&[0xF0, 0x0A, 0xF1, 0x0A]);
+    cpu.set_input_queue(true);
+
+    // Tapped between two cycles, so the key is already up again.
+    cpu.set_key_at_index(0x7, true);
+    cpu.queue_key_press(0x7);
+    cpu.set_key_at_index(0x7, false);
+
+    cpu.execute_cycle();
+
+    assert_eq!(cpu[0x0], 0x7);
+    assert_eq!(cpu.pc, 0x202);
+
+    // The press was consumed, so the next wait keeps waiting.
+    cpu.execute_cycle();
+
+    assert_eq!(cpu.pc, 0x202);
+}
+
+#[test]
+fn queued_presses_are_ignored_unless_enabled() {
+    // 0x200: LD V0, K
+    let mut cpu = cpu_with_rom(&[0xF0, 0x0A]);
+
+    cpu.queue_key_press(0x7);
+    cpu.execute_cycle();
+
+    assert_eq!(cpu.pc, 0x200);
+}
+
+/// Run `LD [I], V3` followed by `LD V3, [I]` with I = 0x300,
+/// and return I after each of them.
+fn load_store_index(mode: LoadStoreMode) -> (usize, usize) {
+    // 0x200: LD [I], V3
+    // 0x202: LD V3, [I]
+    let mut cpu = cpu_with_rom(&[0xF3, 0x55, 0xF3, 0x65]);
+    cpu.set_load_store_mode(mode);
+    cpu.id = 0x300;
+
+    cpu.execute_cycle().unwrap();
+    let after_store = cpu.id;
+
+    cpu.id = 0x300;
+    cpu.execute_cycle().unwrap();
+
+    (after_store, cpu.id)
+}
+
+#[test]
+fn load_store_increments_by_x_plus_one() {
+    assert_eq!(
+        load_store_index(LoadStoreMode::IncrementByXPlus1),
+        (0x304, 0x304)
+    );
+}
+
+#[test]
+fn load_store_increments_by_x() {
+    assert_eq!(
+        load_store_index(LoadStoreMode::IncrementByX),
+        (0x303, 0x303)
+    );
+}
+
+#[test]
+fn load_store_leaves_index_unchanged() {
+    assert_eq!(load_store_index(LoadStoreMode::Unchanged), (0x300, 0x300));
+}
+
+#[test]
+fn load_store_bool_maps_to_mode() {
+    let mut cpu = Ferrous::new();
+
+    cpu.set_load_store(true);
+    assert_eq!(cpu.load_store_mode, LoadStoreMode::Unchanged);
+
+    cpu.set_load_store(false);
+    assert_eq!(cpu.load_store_mode, LoadStoreMode::IncrementByXPlus1);
+}
+
+#[test]
+fn sound_timer_emits_sound_on() {
+    // 0x200: LD V0, 0x02
+    // 0x202: LD ST, V0
+    let mut cpu = cpu_with_rom(&[0x60, 0x02, 0xF0, 0x18]);
+
+    cpu.execute_cycle().unwrap();
+    assert!(cpu.drain_events().is_empty());
+
+    cpu.execute_cycle().unwrap();
+    assert_eq!(cpu.drain_events(), [CpuEvent::SoundOn]);
+    assert!(cpu.drain_events().is_empty());
+}
+
+#[test]
+fn sound_off_is_emitted_once_the_beep_ends() {
+    // 0x200: LD V0, 0x01
+    // 0x202: LD ST, V0
+    let mut cpu = cpu_with_rom(&[0x60, 0x01, 0xF0, 0x18]);
+
+    cpu.execute_cycle().unwrap();
+    cpu.execute_cycle().unwrap();
+    cpu.drain_events();
+
+    // The beep is latched for the tick in which the timer reached zero.
+    cpu.step_timers();
+    assert!(cpu.drain_events().is_empty());
+
+    cpu.step_timers();
+    assert_eq!(cpu.drain_events(), [CpuEvent::SoundOff]);
+}
+
+/// Execute the invalid opcode 0x5001 under the given policy.
+fn execute_invalid_opcode(policy: InvalidOpcodePolicy) -> Ferrous {
+    // 0x200: 0x5001, invalid as 5xy_ requires a low nibble of 0.
+    let mut cpu = cpu_with_rom(&[0x50, 0x01]);
+    cpu.set_invalid_opcode_policy(policy);
+
+    assert_eq!(cpu.execute_cycle(), None);
+
+    cpu
+}
+
+#[test]
+fn ignore_policy_skips_invalid_opcodes() {
+    let mut cpu = execute_invalid_opcode(InvalidOpcodePolicy::Ignore);
+
+    assert_eq!(cpu.pc, 0x202);
+    assert!(!cpu.is_halted);
+    assert!(cpu.drain_events().is_empty());
+}
+
+#[test]
+fn halt_policy_halts_on_invalid_opcodes() {
+    let mut cpu = execute_invalid_opcode(InvalidOpcodePolicy::Halt);
+
+    assert_eq!(cpu.pc, 0x202);
+    assert!(cpu.is_halted);
+    assert_eq!(cpu.drain_events(), [CpuEvent::Halt]);
+    assert_eq!(cpu.execute_cycle(), None);
+    assert_eq!(cpu.pc, 0x202);
+}
+
+#[test]
+#[should_panic(expected = "Invalid or unknown opcode 0x5001 at 0x200.")]
+fn panic_policy_panics_on_invalid_opcodes() {
+    execute_invalid_opcode(InvalidOpcodePolicy::Panic);
+}
+
+#[test]
+fn short_beeps_last_a_full_tick_under_sub_frame_deltas() {
+    // 0x200: LD V0, 0x01
+    // 0x202: LD ST, V0
+    let mut cpu = cpu_with_rom(&[0x60, 0x01, 0xF0, 0x18]);
+    let mut clock = TickClock::new();
+
+    // The ROM sets a sound timer of 1 halfway into a tick.
+    clock.advance(TICK_TIME / 2);
+    cpu.execute_cycle().unwrap();
+    cpu.execute_cycle().unwrap();
+
+    // Frames drawn at four times the tick rate.
+    let delta = TICK_TIME / 4;
+    let mut audible = core::time::Duration::ZERO;
+
+    for _ in 0..16 {
+        if cpu.should_beep() {
+            audible += delta;
+        }
+
+        clock.advance(delta);
+
+        while clock.next_tick(0).is_some() {
+            cpu.step_timers();
+        }
+    }
+
+    assert!(audible >= TICK_TIME);
+    assert!(!cpu.should_beep());
+}
+
+#[test]
+fn mode_change_and_halt_are_emitted() {
+    // 0x200: HIGH
+    // 0x202: HIGH
+    // 0x204: EXIT
+    let mut cpu = cpu_with_rom(&[0x00, 0xFF, 0x00, 0xFF, 0x00, 0xFD]);
+
+    for _ in 0..3 {
+        cpu.execute_cycle().unwrap();
+    }
+
+    assert_eq!(
+        cpu.drain_events(),
+        [CpuEvent::ModeChange { is_highres: true }, CpuEvent::Halt]
+    );
+}
+
+#[test]
+fn set_high_res_clears_the_screen() {
+    let mut cpu = Ferrous::new();
+    cpu.vram[5] = 1;
+
+    cpu.set_high_res(true);
+
+    assert!(cpu.is_highres);
+    assert!(cpu.vram.iter().all(|&pixel| pixel == 0));
+    assert_eq!(
+        cpu.drain_events(),
+        [CpuEvent::ModeChange { is_highres: true }]
+    );
+}
+
+#[test]
+fn reload_rom_keeps_interpreter_state() {
+    // 0x200: LD V0, 0x2A
+    // 0x202: LD V1, 0x17
+    let mut cpu = cpu_with_rom(&[0x60, 0x2A, 0x61, 0x17]);
+
+    cpu.execute_cycle().unwrap();
+    cpu.execute_cycle().unwrap();
+
+    // 0x200: LD V2, 0x01
+    cpu.reload_rom(&[0x62, 0x01]).unwrap();
+
+    assert_eq!(cpu.reg[0], 0x2A);
+    assert_eq!(cpu.reg[1], 0x17);
+    assert_eq!(cpu.pc, 0x204);
+    assert_eq!(&cpu.ram[0x200..0x204], &[0x62, 0x01, 0x00, 0x00]);
+    assert_eq!(cpu.rom_len(), 2);
+}
+
+#[test]
+fn vram_rows_match_resolution() {
+    // 0x200: HIGH
+    let mut cpu = cpu_with_rom(&[0x00, 0xFF]);
+
+    assert_eq!(cpu.vram_rows().count(), 32);
+    assert!(cpu.vram_rows().all(|row| row.len() == 64));
+
+    cpu.execute_cycle().unwrap();
+
+    assert_eq!(cpu.get_height_width(), (64, 128));
+    assert_eq!(cpu.vram_rows().count(), 64);
+    assert!(cpu.vram_rows().all(|row| row.len() == 128));
+}
+
+#[test]
+fn sprites_are_placed_by_the_current_resolution() {
+    // 0x200: DRW V0, V0, 1, 0x202: HIGH, 0x204: DRW V0, V0, 1
+    let mut cpu = cpu_with_rom(&[0xD0, 0x01, 0x00, 0xFF, 0xD0, 0x01]);
+
+    cpu[0x0] = 1;
+    cpu.id = 0x300;
+    cpu.ram[0x300] = 0x80;
+
+    cpu.execute_cycle().unwrap();
+
+    assert_eq!(cpu.resolution(), Resolution::LOW);
+    assert_eq!(cpu.vram[Resolution::LOW.index(1, 1)], 1);
+    assert_eq!(cpu.vram_rows().nth(1).unwrap()[1], 1);
+
+    cpu.execute_cycle().unwrap();
+    cpu.execute_cycle().unwrap();
+
+    assert_eq!(cpu.resolution(), Resolution::HIGH);
+    assert_eq!(cpu.vram[Resolution::HIGH.index(1, 1)], 1);
+    assert_eq!(cpu.vram.iter().filter(|&&pixel| pixel != 0).count(), 1);
+    assert_eq!(cpu.vram_rows().nth(1).unwrap()[1], 1);
+}
+
+#[test]
+fn resolution_rows_are_contiguous() {
+    let resolution = Resolution::LOW;
+
+    assert_eq!(resolution.pixels(), 64 * 32);
+    assert_eq!(resolution.row(0), 0..64);
+    assert_eq!(resolution.row(31).end, resolution.pixels());
+    assert_eq!(Resolution::MAX.pixels(), VRAM_SIZE);
+}
+
+#[test]
+fn skip_delays_zeroes_delay_timer_in_wait_loop() {
+    // 0x200: LD V3, DT
+    // 0x202: SE V3, 0x00
+    // 0x204: JP 0x200
+    let rom = [0xF3, 0x07, 0x33, 0x00, 0x12, 0x00];
+
+    let mut cpu = cpu_with_rom(&rom);
+    cpu.dt = 0x20;
+    cpu.execute_cycle().unwrap();
+    assert_eq!(cpu.reg[3], 0x20);
+
+    let mut cpu = cpu_with_rom(&rom);
+    cpu.set_skip_delays(true);
+    cpu.dt = 0x20;
+    cpu.execute_cycle().unwrap();
+    assert_eq!((cpu.dt, cpu.reg[3]), (0, 0));
+}
+
+#[test]
+fn skip_delays_ignores_other_delay_reads() {
+    // 0x200: LD V3, DT
+    // 0x202: SE V3, 0x00
+    // 0x204: JP 0x300
+    let mut cpu = cpu_with_rom(&[0xF3, 0x07, 0x33, 0x00, 0x13, 0x00]);
+    cpu.set_skip_delays(true);
+    cpu.dt = 0x20;
+
+    cpu.execute_cycle().unwrap();
+    assert_eq!(cpu.reg[3], 0x20);
+}
+
+#[test]
+fn draws_sprite_from_arbitrary_address() {
+    // 0x200: LD I, 0x5A3
+    // 0x202: DRW V0, V1, 2
+    let mut cpu = cpu_with_rom(&[0xA5, 0xA3, 0xD0, 0x12]);
+    cpu.set_memory_region(0x5A3, &[0b1000_0001, 0b0100_0010])
+        .unwrap();
+
+    cpu.reg[0] = 8;
+    cpu.reg[1] = 4;
+
+    cpu.execute_cycle().unwrap();
+    cpu.execute_cycle().unwrap();
+
+    let rows: Vec<_> = cpu.vram_rows().skip(4).take(2).collect();
+    assert_eq!(&rows[0][8..16], &[1, 0, 0, 0, 0, 0, 0, 1]);
+    assert_eq!(&rows[1][8..16], &[0, 1, 0, 0, 0, 0, 1, 0]);
+}
+
+#[test]
+fn memory_region_is_bounds_checked() {
+    let mut cpu = Ferrous::new();
+
+    assert!(cpu.set_memory_region(0xFFE, &[1, 2]).is_ok());
+    assert!(cpu.set_memory_region(0xFFF, &[1, 2]).is_err());
+    assert!(cpu.set_memory_region(usize::MAX, &[1]).is_err());
+}
+
+#[test]
+fn with_memory_uses_the_whole_image() {
+    let mut memory = [0; 0x1000];
+
+    // 0x200: LD V0, 0x07
+    memory[0x200] = 0x60;
+    memory[0x201] = 0x07;
+    memory[0x000] = 0xAA;
+
+    let mut cpu = Ferrous::with_memory(&memory);
+    assert_eq!(cpu.ram[0x000], 0xAA);
+
+    cpu.execute_cycle().unwrap();
+    assert_eq!(cpu.reg[0], 0x07);
+}
+
+#[test]
+#[cfg(feature = "savestates")]
+fn savestate_restores_screen() {
+    // 0x200: HIGH
+    // 0x202: LD F, V0
+    // 0x204: DRW V1, V2, 5
+    let mut cpu = cpu_with_rom(&[0x00, 0xFF, 0xF0, 0x29, 0xD1, 0x25]);
+    cpu.reg[0] = 0x8;
+    cpu.reg[1] = 100;
+    cpu.reg[2] = 40;
+    cpu.set_key_at_index(0x3, true);
+    cpu.set_flag_registers(&[1, 2, 3, 4, 5, 6, 7, 8]);
+
+    for _ in 0..3 {
+        cpu.execute_cycle().unwrap();
+    }
+
+    let serialized = serde_json::to_vec(&cpu).unwrap();
+    let restored: Ferrous = serde_json::from_slice(&serialized).unwrap();
+
+    assert!(restored.vram.iter().any(|&pixel| pixel != 0));
+    assert_eq!(restored.get_height_width(), cpu.get_height_width());
+    assert!(restored.vram_rows().eq(cpu.vram_rows()));
+
+    assert_eq!(restored.ram, cpu.ram);
+    assert_eq!(restored.stack, cpu.stack);
+    assert_eq!(restored.reg, cpu.reg);
+    assert_eq!(restored.keypad, cpu.keypad);
+    assert_eq!(restored.flag_registers(), cpu.flag_registers());
+}
+
+#[test]
+#[cfg(feature = "savestates")]
+fn savestate_maps_the_old_load_store_quirk() {
+    let mut cpu = Ferrous::new();
+    cpu.set_load_store_mode(LoadStoreMode::IncrementByX);
+
+    // Savestates made before load/store modes stored a boolean quirk instead.
+    let mut state = serde_json::to_value(&cpu).unwrap();
+    let fields = state.as_object_mut().unwrap();
+    fields.remove("load_store_mode");
+
+    for &(quirk, mode) in &[
+        (true, LoadStoreMode::Unchanged),
+        (false, LoadStoreMode::IncrementByXPlus1),
+    ] {
+        fields.insert("load_store_quirk".into(), quirk.into());
+
+        let restored: Ferrous = serde_json::from_value(fields.clone().into()).unwrap();
+        assert_eq!(restored.load_store_mode, mode);
+    }
+
+    let serialized = serde_json::to_vec(&cpu).unwrap();
+    let restored: Ferrous = serde_json::from_slice(&serialized).unwrap();
+    assert_eq!(restored.load_store_mode, LoadStoreMode::IncrementByX);
+}
+
+#[test]
+fn detects_chip8_rom() {
+    // CLS, LD V0, 0x05, DRW V0, V0, 5, JP 0x206
+    let rom = [0x00, 0xE0, 0x60, 0x05, 0xD0, 0x05, 0x12, 0x06];
+
+    assert_eq!(Ferrous::detect_feature_set(&rom), FeatureSet::default());
+}
+
+#[test]
+fn detects_super_chip_features() {
+    // LD HF, V1, SCD 4
+    let low_res = Ferrous::detect_feature_set(&[0xF1, 0x30, 0x00, 0xC4]);
+
+    // HIGH, LD R, V3
+    let high_res = Ferrous::detect_feature_set(&[0x00, 0xFF, 0xF3, 0x75]);
+
+    assert!(low_res.super_chip && !low_res.high_res && !low_res.xo_chip);
+    assert!(high_res.super_chip && high_res.high_res && !high_res.xo_chip);
+}
+
+#[test]
+fn detects_xo_chip_features() {
+    // LD I, long 0x1234
+    let long_index = Ferrous::detect_feature_set(&[0xF0, 0x00, 0x12, 0x34]);
+
+    // SAVE V1 - V4
+    let save_range = Ferrous::detect_feature_set(&[0x51, 0x42]);
+
+    assert!(long_index.xo_chip && !long_index.super_chip);
+    assert!(save_range.xo_chip && !save_range.super_chip);
+}
+
+#[test]
+fn step_n_stops_at_breakpoint() {
+    // 0x200: LD V0, 0x01
+    // 0x202: LD V1, 0x02
+    // 0x204: LD V2, 0x03
+    // 0x206: LD V3, 0x04
+    let mut cpu = cpu_with_rom(&[0x60, 0x01, 0x61, 0x02, 0x62, 0x03, 0x63, 0x04]);
+    cpu.add_breakpoint(0x204);
+
+    let summary = cpu.step_n(10);
+    assert_eq!(summary.executed, 2);
+    assert_eq!(summary.stopped, Some(StopReason::Breakpoint(0x204)));
+    assert_eq!(cpu.reg[2], 0);
+
+    // Resuming from the breakpoint executes past it.
+    let summary = cpu.step_n(2);
+    assert_eq!(summary.executed, 2);
+    assert_eq!(summary.stopped, None);
+    assert_eq!(cpu.reg[3], 0x04);
+}
+
+#[test]
+fn temporary_breakpoint_is_removed_once_hit() {
+    // 0x200: LD V0, 0x01
+    // 0x202: JP 0x200
+    let mut cpu = cpu_with_rom(&[0x60, 0x01, 0x12, 0x00]);
+    cpu.set_temporary_breakpoint(0x202);
+    assert!(cpu.is_breakpoint(0x202));
+
+    let summary = cpu.step_n(10);
+    assert_eq!(summary.executed, 1);
+    assert_eq!(summary.stopped, Some(StopReason::Breakpoint(0x202)));
+    assert_eq!(cpu.temporary_breakpoint(), None);
+
+    // The loop passes the address again, but doesn't stop there anymore.
+    let summary = cpu.step_n(10);
+    assert_eq!(summary.executed, 10);
+    assert_eq!(summary.stopped, None);
+    assert!(!cpu.is_breakpoint(0x202));
+}
+
+#[test]
+fn temporary_breakpoint_respects_regular_breakpoints() {
+    // 0x200: LD V0, 0x01
+    // 0x202: LD V1, 0x02
+    // 0x204: LD V2, 0x03
+    let mut cpu = cpu_with_rom(&[0x60, 0x01, 0x61, 0x02, 0x62, 0x03]);
+    cpu.add_breakpoint(0x202);
+    cpu.set_temporary_breakpoint(0x204);
+
+    let summary = cpu.step_n(10);
+    assert_eq!(summary.stopped, Some(StopReason::Breakpoint(0x202)));
+    assert_eq!(cpu.temporary_breakpoint(), Some(0x204));
+
+    let summary = cpu.step_n(10);
+    assert_eq!(summary.stopped, Some(StopReason::Breakpoint(0x204)));
+    assert_eq!(cpu.temporary_breakpoint(), None);
+}
+
+#[test]
+fn step_n_reports_invalid_opcode_and_halt() {
+    // 0x200: LD V0, 0x01
+    // 0x202: invalid
+    let mut cpu = cpu_with_rom(&[0x60, 0x01, 0x5F, 0xF1]);
+
+    let summary = cpu.step_n(10);
+    assert_eq!(summary.executed, 2);
+    assert_eq!(summary.stopped, Some(StopReason::InvalidOpcode(0x5FF1)));
+
+    // 0x200: EXIT
+    let mut cpu = cpu_with_rom(&[0x00, 0xFD]);
+
+    let summary = cpu.step_n(10);
+    assert_eq!(summary.executed, 1);
+    assert_eq!(summary.stopped, Some(StopReason::Halted));
+}
+
+#[test]
+fn instructions_are_categorized() {
+    let category = |opcode| Instruction::decode(opcode).unwrap().category();
+
+    assert_eq!(category(0x1234), Category::Flow);
+    assert_eq!(category(0x3A05), Category::Flow);
+    assert_eq!(category(0xA300), Category::Load);
+    assert_eq!(category(0xF365), Category::Load);
+    assert_eq!(category(0x8124), Category::Arithmetic);
+    assert_eq!(category(0xC0FF), Category::Arithmetic);
+    assert_eq!(category(0xD125), Category::Draw);
+    assert_eq!(category(0x00FF), Category::Draw);
+    assert_eq!(category(0xE19E), Category::Io);
+    assert_eq!(category(0xF20A), Category::Io);
+}
+
+/// Place the program at 0x200 in otherwise empty memory,
+/// and disassemble it by following its control flow.
+fn code_addresses(program: &[u8]) -> (Vec<usize>, Listing) {
+    let mut memory = vec![0; 0x200];
+    memory.extend_from_slice(program);
+
+    let listing = trace_disassemble(&memory, 0x200);
+    let code = listing
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ListingItem::Code { address, .. } => Some(*address),
+            ListingItem::Data { .. } => None,
+        })
+        .collect();
+
+    (code, listing)
+}
+
+#[test]
+fn trace_disassembly_follows_branches() {
+    let (code, listing) = code_addresses(&[
+        0x60, 0x01, // 0x200: LD V0, 0x01
+        0x30, 0x01, // 0x202: SE V0, 0x01
+        0x12, 0x0A, // 0x204: JP 0x20A
+        0x22, 0x10, // 0x206: CALL 0x210
+        0x12, 0x08, // 0x208: JP 0x208
+        0x12, 0x06, // 0x20A: JP 0x206
+        0xFF, 0x81, // 0x20C: Sprite data
+        0x12, 0x34, // 0x20E: Unreachable
+        0x00, 0xEE, // 0x210: RET
+    ]);
+
+    assert_eq!(code, [0x200, 0x202, 0x204, 0x206, 0x208, 0x20A, 0x210]);
+    assert_eq!(
+        listing.labels.into_iter().collect::<Vec<_>>(),
+        [0x206, 0x208, 0x20A, 0x210]
+    );
+    assert_eq!(
+        listing.items[6],
+        ListingItem::Data {
+            address: 0x20C,
+            byte: 0xFF
+        }
+    );
+}
+
+#[test]
+fn trace_disassembly_only_follows_base_of_computed_jumps() {
+    let (code, listing) = code_addresses(&[
+        0xB2, 0x04, // 0x200: JP V0, 0x204
+        0x00, 0xE0, // 0x202: Unreachable, unless V0 is negative
+        0x00, 0xFD, // 0x204: EXIT
+        0x00, 0xE0, // 0x206: Reachable with V0 = 2, but not followed
+    ]);
+
+    assert_eq!(code, [0x200, 0x204]);
+    assert_eq!(
+        listing.to_string(),
+        "    0x200  B204  JP V0, 0x204\n\
+         \x20   0x202  00    DB 0x00\n\
+         \x20   0x203  E0    DB 0xE0\n\
+         label_0x204:\n\
+         \x20   0x204  00FD  EXIT\n\
+         \x20   0x206  00    DB 0x00\n\
+         \x20   0x207  E0    DB 0xE0\n"
+    );
+}
+
+#[test]
+fn opcode_histogram_counts_reachable_code() {
+    let cpu = cpu_with_rom(&[
+        0x60, 0x05, // 0x200: LD V0, 0x05
+        0x61, 0x0A, // 0x202: LD V1, 0x0A
+        0xA2, 0x0E, // 0x204: LD I, 0x20E
+        0xD0, 0x15, // 0x206: DRW V0, V1, 0x5
+        0x70, 0x08, // 0x208: ADD V0, 0x08
+        0xD0, 0x15, // 0x20A: DRW V0, V1, 0x5
+        0x12, 0x0C, // 0x20C: JP 0x20C
+        0x60, 0x60, // 0x20E: Sprite data, not LD V0, 0x60
+        0x60,
+    ]);
+
+    let histogram = cpu.opcode_histogram();
+
+    assert_eq!(
+        histogram.into_iter().collect::<Vec<_>>(),
+        [
+            ("ADD Vx, byte", 1),
+            ("DRW Vx, Vy, nibble", 2),
+            ("JP addr", 1),
+            ("LD I, addr", 1),
+            ("LD Vx, byte", 2),
+        ]
+    );
+}
+
+#[test]
+fn memory_regions_follow_the_loaded_rom() {
+    let cpu = cpu_with_rom(&[0x00; 4]);
+
+    assert_eq!(cpu.memory_region(0x000), MemoryRegion::LowResFont);
+    assert_eq!(cpu.memory_region(0x04F), MemoryRegion::LowResFont);
+    assert_eq!(cpu.memory_region(0x050), MemoryRegion::HighResFont);
+    assert_eq!(cpu.memory_region(0x0EF), MemoryRegion::HighResFont);
+    assert_eq!(cpu.memory_region(0x1FF), MemoryRegion::Reserved);
+    assert_eq!(cpu.memory_region(0x203), MemoryRegion::Program);
+    assert_eq!(cpu.memory_region(0x204), MemoryRegion::Data);
+}
+
+#[test]
+fn reset_to_rom_restores_the_program() {
+    // 0x200: LD V0, 0x2A
+    let mut cpu = cpu_with_rom(&[0x60, 0x2A]);
+    cpu.execute_cycle();
+
+    cpu.ram[0x200] = 0xFF;
+    cpu.ram[0x300] = 0x12;
+    cpu.reset_to_rom().unwrap();
+
+    assert_eq!(cpu.memory_slice(0x200..0x202), Some(&[0x60, 0x2A][..]));
+    assert_eq!(cpu.ram[0x300], 0);
+    assert_eq!(cpu.reg[0], 0);
+    assert_eq!(cpu.pc, 0x200);
+    assert_eq!(cpu.rom_len(), 2);
+}
+
+#[test]
+fn replaying_draw_events_reproduces_the_display() {
+    let mut cpu = cpu_with_rom(&[
+        0x00, 0xFF, // 0x200: HIGH
+        0xA0, 0x00, // 0x202: LD I, 0x000
+        0x60, 0x7E, // 0x204: LD V0, 0x7E
+        0xD0, 0x05, // 0x206: DRW V0, V0, 0x5
+        0xD0, 0x05, // 0x208: DRW V0, V0, 0x5
+        0x00, 0xC3, // 0x20A: SCD 0x3
+        0xD0, 0x00, // 0x20C: DRW V0, V0, 0x0
+        0x00, 0xFB, // 0x20E: SCR
+    ]);
+
+    cpu.set_record_draw_events(true);
+
+    for _ in 0..8 {
+        cpu.execute_cycle();
+    }
+
+    let events = cpu.drain_draw_events();
+
+    assert_eq!(events.len(), 7);
+    assert_eq!(events[0], DrawEvent::Resolution { is_highres: true });
+    assert_eq!(events[1], DrawEvent::Clear);
+    assert!(matches!(
+        events[3],
+        DrawEvent::Sprite {
+            x: 0x7E,
+            y: 0x7E,
+            wide: false,
+            collision: true,
+            ..
+        }
+    ));
+
+    let mut spectator = Ferrous::new();
+
+    for event in &events {
+        spectator.apply_draw_event(event);
+    }
+
+    assert!(spectator.is_highres);
+    assert_eq!(spectator.vram[..], cpu.vram[..]);
+    assert!(cpu.drain_draw_events().is_empty());
+}
+
+#[test]
+fn dirty_region_bounds_drawn_sprites() {
+    let mut cpu = cpu_with_rom(&[
+        0xA0, 0x00, // 0x200: LD I, 0x000 (font sprite "0", 4x5)
+        0x60, 0x0A, // 0x202: LD V0, 0x0A
+        0x61, 0x05, // 0x204: LD V1, 0x05
+        0xD0, 0x15, // 0x206: DRW V0, V1, 0x5
+        0x60, 0x14, // 0x208: LD V0, 0x14
+        0xD0, 0x05, // 0x20A: DRW V0, V0, 0x5
+        0x00, 0xE0, // 0x20C: CLS
+    ]);
+
+    for _ in 0..3 {
+        cpu.execute_cycle();
+    }
+
+    assert_eq!(cpu.take_dirty_region(), None);
+
+    cpu.execute_cycle();
+    assert_eq!(cpu.dirty_region(), Some((10, 5, 13, 9)));
+
+    // Both sprites are covered by a single box, until it is taken.
+    cpu.execute_cycle();
+    cpu.execute_cycle();
+    assert_eq!(cpu.take_dirty_region(), Some((10, 5, 23, 24)));
+    assert_eq!(cpu.take_dirty_region(), None);
+
+    cpu.execute_cycle();
+    assert_eq!(cpu.take_dirty_region(), Some((0, 0, 63, 31)));
+}
+
+#[test]
+fn dirty_region_wraps_with_the_sprite() {
+    // 0x200: LD V0, 0x3E; 0x202: DRW V0, V0, 0x5 (font sprite "0" at 62, 62)
+    let mut cpu = cpu_with_rom(&[0x60, 0x3E, 0xD0, 0x05]);
+
+    cpu.execute_cycle();
+    cpu.execute_cycle();
+
+    // x wraps from 62..=65 to 62, 63, 0, 1; y from 30 to 30, 31, 0, 1, 2.
+    assert_eq!(cpu.take_dirty_region(), Some((0, 0, 63, 31)));
+}
+
+#[test]
+fn entry_point_is_kept_across_resets() {
+    let mut cpu = Ferrous::new();
+
+    // 0x600: LD V0, 0x2A
+    cpu.load_rom_at(0x600, &[0x60, 0x2A]).unwrap();
+
+    assert_eq!(cpu.pc, 0x600);
+    assert_eq!(cpu.execute_cycle(), Some(0x602A));
+    assert_eq!(cpu.memory_region(0x200), MemoryRegion::Reserved);
+
+    cpu.reset();
+    assert_eq!(cpu.pc, 0x600);
+    assert_eq!(cpu.entry_point(), 0x600);
+
+    assert!(cpu.set_entry_point(0x100).is_err());
+    assert!(cpu.load_rom(&[0; 0xA01]).is_err());
+    assert!(cpu.load_rom(&[0; 0xA00]).is_ok());
+}
+
+#[test]
+fn disasm_window_surrounds_the_program_counter() {
+    // 0x200: LD V0, 0x2A
+    // 0x202: ADD V0, 0x01
+    // 0x204: JP 0x202
+    let mut cpu = cpu_with_rom(&[0x60, 0x2A, 0x70, 0x01, 0x12, 0x02]);
+    cpu.execute_cycle().unwrap();
+
+    assert_eq!(
+        cpu.disasm_window(1, 1),
+        [
+            (0x200, "LD V0, 0x2A".to_string()),
+            (0x202, "ADD V0, 0x01".to_string()),
+            (0x204, "JP 0x202".to_string()),
+        ]
+    );
+
+    // Clamped to the bounds of memory.
+    assert_eq!(cpu.disasm_window(usize::MAX, 0)[0].0, 0x000);
+    assert_eq!(cpu.disasm_window(0, usize::MAX).last().unwrap().0, 0xFFE);
+
+    cpu.pc = 0;
+    assert_eq!(cpu.disasm_window(usize::MAX, usize::MAX).len(), 0x800);
+}
+
+#[test]
+fn moving_the_entry_point_forgets_the_rom() {
+    let mut cpu = Ferrous::new();
+    cpu.load_rom(&[0; 0xE00]).unwrap();
+
+    cpu.set_entry_point(0x600).unwrap();
+
+    assert_eq!(cpu.rom_len(), 0);
+    assert!(cpu.rom_bytes().is_empty());
+    assert!(cpu.opcode_histogram().is_empty());
+    assert!(!cpu.has_self_modified());
+    assert_eq!(cpu.free_memory(), 0xA00);
+
+    assert!(cpu.reset_to_rom().is_ok());
+    assert_eq!(cpu.pc, 0x600);
+
+    assert!(cpu.reload_rom(&[0x60, 0x2A]).is_ok());
+    assert_eq!(cpu.rom_bytes(), [0x60, 0x2A]);
+}
+
+#[test]
+fn reset_to_rom_fails_if_the_rom_no_longer_fits() {
+    let mut cpu = cpu_with_rom(&[0; 0xE00]);
+    cpu.entry_point = 0x600;
+
+    assert!(cpu.reset_to_rom().is_err());
+    assert_eq!(cpu.pc, 0x600);
+}
+
+/// Draws the 0 glyph at random positions, and clears the
+/// display whenever key 5 is held.
+const LOCKSTEP_ROM: [u8; 18] = [
+    0xC0, 0x3F, // RND V0, 0x3F
+    0xC1, 0x1F, // RND V1, 0x1F
+    0xA0, 0x00, // LD I, 0x000
+    0xD0, 0x15, // DRW V0, V1, 5
+    0x62, 0x05, // LD V2, 5
+    0xE2, 0x9E, // SKP V2
+    0x12, 0x00, // JP 0x200
+    0x00, 0xE0, // CLS
+    0x12, 0x00, // JP 0x200
+];
+
+fn lockstep_digest(seed: u64) -> u64 {
+    let mut recording = crate::InputRecording::new(25);
+    recording.record(10, 1 << 5);
+    recording.record(11, 0);
+    recording.record(20, 1 << 5);
+    recording.record(21, 0);
+
+    let mut cpu = cpu_with_rom(&LOCKSTEP_ROM);
+    cpu.enter_lockstep(seed, recording);
+
+    for _ in 0..40 {
+        assert_eq!(cpu.step_lockstep(), Some(0));
+    }
+
+    cpu.vram_digest()
+}
+
+#[test]
+fn lockstep_runs_are_identical() {
+    let digest = lockstep_digest(1234);
+
+    assert_eq!(lockstep_digest(1234), digest);
+    assert_ne!(lockstep_digest(4321), digest);
+}
+
+#[test]
+fn input_diffs_press_and_release_keys() {
+    let mut cpu = Ferrous::new();
+
+    cpu.apply_input_diff(0b1000_0000_0010_0001, 0);
+    assert_eq!(
+        (0..16)
+            .filter(|&key| cpu.key_is_down(key))
+            .collect::<Vec<_>>(),
+        [0x0, 0x5, 0xF]
+    );
+
+    // Keys in neither mask are untouched, keys in both are released.
+    cpu.apply_input_diff(0b0000_0000_0000_0110, 0b0000_0000_0010_0100);
+    assert_eq!(
+        (0..16)
+            .filter(|&key| cpu.key_is_down(key))
+            .collect::<Vec<_>>(),
+        [0x0, 0x1, 0xF]
+    );
+
+    cpu.apply_input_diff(0, 0xFFFF);
+    assert_eq!(cpu.keypad, [false; 16]);
+}
+
+#[test]
+fn input_recording_holds_keys_until_they_change() {
+    let mut recording = crate::InputRecording::new(10);
+    recording.record(2, 0b101);
+    recording.record(3, 0b101);
+    recording.record(5, 0);
+
+    assert_eq!(recording.keys_at(0), 0);
+    assert_eq!(recording.keys_at(2), 0b101);
+    assert_eq!(recording.keys_at(4), 0b101);
+    assert_eq!(recording.keys_at(5), 0);
+    assert_eq!(recording.keys_at(100), 0);
+}
+
+#[test]
+fn step_lockstep_requires_lockstep_mode() {
+    let mut cpu = cpu_with_rom(&LOCKSTEP_ROM);
+    assert_eq!(cpu.step_lockstep(), None);
+
+    cpu.enter_lockstep(0, crate::InputRecording::new(1));
+    assert!(cpu.is_lockstep());
+
+    assert!(cpu.exit_lockstep().is_some());
+    assert_eq!(cpu.step_lockstep(), None);
+}
+
+#[test]
+fn writing_into_the_program_is_detected() {
+    // 0x200: LD I, 0x208
+    // 0x202: LD V0, 0xFF
+    // 0x204: LD [I], V0
+    // 0x206: JP 0x206
+    // 0x208: DW 0x0000
+    let mut cpu = cpu_with_rom(&[0xA2, 0x08, 0x60, 0xFF, 0xF0, 0x55, 0x12, 0x06, 0x00, 0x00]);
+    let checksum = cpu.memory_checksum();
+
+    cpu.execute_cycle();
+    cpu.execute_cycle();
+    assert!(!cpu.has_self_modified());
+
+    cpu.execute_cycle();
+    assert!(cpu.has_self_modified());
+    assert_ne!(cpu.memory_checksum(), checksum);
+}
+
+#[test]
+fn nothing_is_self_modified_without_a_rom() {
+    let mut cpu = Ferrous::new();
+    cpu.ram[0x200] = 0xFF;
+
+    assert!(!cpu.has_self_modified());
+}
+
+#[test]
+fn display_snapshot_matches_the_video_buffer() {
+    let mut cpu = Ferrous::new();
+    cpu.vram[5] = 1;
+    cpu.vram[64 * 31 + 63] = 1;
+
+    let snapshot = cpu.clone_display_only();
+    assert_eq!(snapshot.resolution, Resolution::LOW);
+    assert_eq!(snapshot.pixels, &cpu.get_video_buffer()[..64 * 32]);
+
+    cpu.switch_resolution(true);
+    cpu.vram[128 * 63 + 127] = 1;
+
+    let snapshot = cpu.clone_display_only();
+    assert!(snapshot.is_highres());
+    assert_eq!(snapshot.pixels, &cpu.get_video_buffer()[..128 * 64]);
+    assert!(snapshot.rows().eq(cpu.vram_rows()));
+}
+
+#[test]
+fn rom_bytes_match_the_loaded_rom() {
+    let rom = [0x60, 0x2A, 0x12, 0x02];
+
+    let mut cpu = Ferrous::new();
+    assert!(cpu.rom_bytes().is_empty());
+
+    cpu.load_rom_at(0x300, &rom).unwrap();
+    assert_eq!(cpu.rom_bytes(), rom);
+}
+
+#[test]
+fn timers_round_trip() {
+    let mut cpu = Ferrous::new();
+    cpu.set_timers(30, 12);
+
+    assert_eq!(cpu.delay_timer(), 30);
+    assert_eq!(cpu.sound_timer(), 12);
+    assert_eq!(cpu.drain_events(), [CpuEvent::SoundOn]);
+
+    cpu.set_delay_timer(1);
+    cpu.set_sound_timer(0);
+
+    assert_eq!(cpu.delay_timer(), 1);
+    assert_eq!(cpu.sound_timer(), 0);
+    assert_eq!(cpu.drain_events(), [CpuEvent::SoundOff]);
+}
+
+#[test]
+fn read_sprite_returns_the_bytes_at_i() {
+    let mut cpu = Ferrous::new();
+    cpu.id = 0x300;
+
+    for (offset, byte) in cpu.ram[0x300..0x320].iter_mut().enumerate() {
+        *byte = offset as u8 + 1;
+    }
+
+    assert_eq!(cpu.read_sprite(3), [1, 2, 3]);
+    assert_eq!(cpu.read_sprite(0), (1..=32).collect::<Vec<u8>>());
+}
+
+#[test]
+fn read_sprite_stops_at_the_end_of_memory() {
+    let mut cpu = Ferrous::new();
+    cpu.ram[0xFFE..].copy_from_slice(&[0xAA, 0xBB]);
+
+    cpu.id = 0xFFE;
+    assert_eq!(cpu.read_sprite(5), [0xAA, 0xBB]);
+    assert_eq!(cpu.read_sprite(0), [0xAA, 0xBB]);
+
+    cpu.id = 0x1200;
+    assert!(cpu.read_sprite(5).is_empty());
+}
+
+#[test]
+fn tick_clock_hands_out_whole_ticks() {
+    let mut clock = TickClock::new();
+
+    // A 144 Hz frontend runs a tick on only some of its frames.
+    clock.advance(TICK_TIME / 2);
+    assert_eq!(clock.next_tick(600), None);
+
+    clock.advance(TICK_TIME / 2);
+    assert_eq!(clock.next_tick(600), Some(10));
+    assert_eq!(clock.next_tick(600), None);
+
+    // A 30 Hz frontend runs two ticks per frame.
+    clock.advance(TICK_TIME * 2);
+    assert_eq!(clock.next_tick(600), Some(10));
+    assert_eq!(clock.next_tick(600), Some(10));
+    assert_eq!(clock.next_tick(600), None);
+
+    clock.advance(TICK_TIME * 3);
+    clock.reset();
+    assert_eq!(clock.next_tick(600), None);
+}
+
+#[test]
+fn tick_clock_carries_partial_instructions() {
+    let mut clock = TickClock::new();
+    clock.advance(TICK_TIME * 60);
+
+    // 700 instructions per second is 11.67 per tick.
+    let cycles: Vec<u16> = core::iter::from_fn(|| clock.next_tick(700)).collect();
+
+    assert_eq!(cycles.len(), 60);
+    assert_eq!(cycles.iter().map(|&c| c as u32).sum::<u32>(), 700);
+    assert!(cycles.iter().all(|&c| c == 11 || c == 12));
+}
+
+#[test]
+fn quirk_profiles_set_their_speed() {
+    let profiles = [
+        (QuirkProfile::Cosmac, 700, 11),
+        (QuirkProfile::SchipLegacy, 1000, 16),
+        (QuirkProfile::SchipModern, 1800, 30),
+        (QuirkProfile::XoChip, 6000, 100),
+    ];
+
+    assert_eq!(Ferrous::new().cycles_per_tick(), 10);
+
+    for &(profile, instructions_per_second, cycles_per_tick) in profiles.iter() {
+        let mut cpu = Ferrous::new();
+        cpu.apply_profile(profile);
+
+        assert_eq!(cpu.instructions_per_second, instructions_per_second);
+        assert_eq!(cpu.cycles_per_tick(), cycles_per_tick, "{:?}", profile);
+    }
+}
+
+#[test]
+fn traced_cycles_report_register_and_flag_changes() {
+    let mut cpu = cpu_with_rom(&[0x80, 0x14]); // ADD V0, V1
+    cpu.reg[0] = 0xFF;
+    cpu.reg[1] = 0x02;
+
+    let delta = cpu.execute_cycle_traced();
+
+    assert_eq!(delta.opcode, Some(0x8014));
+    assert_eq!(delta.pc, (0x200, 0x202));
+    assert_eq!(delta.registers, [(0x0, 0xFF, 0x01), (0xF, 0x00, 0x01)]);
+    assert!(delta.memory.is_empty() && delta.pixels.is_empty());
+}
+
+#[test]
+fn traced_cycles_report_memory_and_stack_changes() {
+    let mut cpu = cpu_with_rom(&[
+        0x22, 0x04, // 0x200: CALL 0x204
+        0x00, 0x00, // 0x202: Padding
+        0xF0, 0x33, // 0x204: LD B, V0
+    ]);
+    cpu.reg[0] = 123;
+    cpu.id = 0x300;
+
+    let call = cpu.execute_cycle_traced();
+
+    assert_eq!(call.pc, (0x200, 0x204));
+    assert_eq!(call.stack_pointer, Some((0, 1)));
+    assert_eq!(call.stack, [(0, 0x000, 0x202)]);
+
+    let bcd = cpu.execute_cycle_traced();
+
+    assert_eq!(bcd.memory, [(0x300, 0, 1), (0x301, 0, 2), (0x302, 0, 3)]);
+    assert_eq!(bcd.index, None);
+}
+
+#[test]
+fn traced_cycles_report_flipped_pixels() {
+    let mut cpu = cpu_with_rom(&[
+        0xD0, 0x11, // 0x200: DRW V0, V1, 0x1
+        0x00, 0xFD, // 0x202: EXIT
+    ]);
+    cpu.id = 0x300;
+    cpu.ram[0x300] = 0b1010_0000;
+
+    let draw = cpu.execute_cycle_traced();
+
+    assert_eq!(draw.pixels, [0, 2]);
+    assert_eq!(draw.registers, []);
+    assert!(!draw.halted);
+
+    assert!(cpu.execute_cycle_traced().halted);
+
+    let halted = cpu.execute_cycle_traced();
+
+    assert_eq!(halted.opcode, None);
+    assert_eq!(
+        halted,
+        CycleDelta {
+            pc: halted.pc,
+            ..CycleDelta::default()
+        }
+    );
+}
@@ -0,0 +1,71 @@
+//! Property based tests for the 8xyN arithmetic and logic opcodes.
+
+use proptest::prelude::*;
+
+use super::*;
+
+/// Execute `8xyN` with x = 1 and y = 2, and the given register values.
+fn execute_8xyn(n: u8, vx: u8, vy: u8, shift_quirk: bool) -> Ferrous {
+    let mut cpu = cpu_with_rom(&[0x81, 0x20 | n]);
+    cpu.set_shift(shift_quirk);
+    cpu.reg[1] = vx;
+    cpu.reg[2] = vy;
+
+    cpu.execute_cycle().unwrap();
+
+    cpu
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    #[test]
+    fn logic_ops_match_bitwise_ops(vx: u8, vy: u8) {
+        prop_assert_eq!(execute_8xyn(0x1, vx, vy, false).reg[1], vx | vy);
+        prop_assert_eq!(execute_8xyn(0x2, vx, vy, false).reg[1], vx & vy);
+        prop_assert_eq!(execute_8xyn(0x3, vx, vy, false).reg[1], vx ^ vy);
+    }
+
+    #[test]
+    fn add_sets_carry(vx: u8, vy: u8) {
+        let cpu = execute_8xyn(0x4, vx, vy, false);
+        let (sum, carry) = vx.overflowing_add(vy);
+
+        prop_assert_eq!(cpu.reg[1], sum);
+        prop_assert_eq!(cpu.reg[0xF], carry as u8);
+    }
+
+    #[test]
+    fn sub_sets_not_borrow(vx: u8, vy: u8) {
+        let cpu = execute_8xyn(0x5, vx, vy, false);
+
+        prop_assert_eq!(cpu.reg[1], vx.wrapping_sub(vy));
+        prop_assert_eq!(cpu.reg[0xF], (vx >= vy) as u8);
+    }
+
+    #[test]
+    fn subn_sets_not_borrow(vx: u8, vy: u8) {
+        let cpu = execute_8xyn(0x7, vx, vy, false);
+
+        prop_assert_eq!(cpu.reg[1], vy.wrapping_sub(vx));
+        prop_assert_eq!(cpu.reg[0xF], (vy >= vx) as u8);
+    }
+
+    #[test]
+    fn shr_shifts_out_lowest_bit(vx: u8, vy: u8, shift_quirk: bool) {
+        let cpu = execute_8xyn(0x6, vx, vy, shift_quirk);
+        let source = if shift_quirk { vx } else { vy };
+
+        prop_assert_eq!(cpu.reg[1], source >> 1);
+        prop_assert_eq!(cpu.reg[0xF], source & 1);
+    }
+
+    #[test]
+    fn shl_shifts_out_highest_bit(vx: u8, vy: u8, shift_quirk: bool) {
+        let cpu = execute_8xyn(0xE, vx, vy, shift_quirk);
+        let source = if shift_quirk { vx } else { vy };
+
+        prop_assert_eq!(cpu.reg[1], source << 1);
+        prop_assert_eq!(cpu.reg[0xF], source >> 7);
+    }
+}
@@ -0,0 +1,70 @@
+//! Property based tests comparing Dxyn against a per-pixel reference.
+
+use proptest::prelude::*;
+
+use super::*;
+
+/// Draw the sprite one pixel at a time, the straightforward way,
+/// returning the expected VRAM and collision flag.
+fn reference_draw(cpu: &Ferrous, x: u8, y: u8, sprite: &[u8], wide: bool) -> (Vec<u8>, bool) {
+    let (rows, cols) = cpu.get_height_width();
+    let width = if wide { 16 } else { 8 };
+
+    let mut vram = cpu.vram.to_vec();
+    let mut collision = false;
+
+    for r in 0..sprite.len() * 8 / width {
+        for c in 0..width {
+            let byte = sprite[r * width / 8 + c / 8];
+
+            if byte & (0x80 >> (c % 8)) != 0 {
+                let (px, py) = (x as usize % cols + c, y as usize % rows + r);
+
+                if (px >= cols && !cpu.wrap_x) || (py >= rows && !cpu.wrap_y) {
+                    continue;
+                }
+
+                let index = (px % cols) + (py % rows) * cols;
+
+                collision |= vram[index] == 1;
+                vram[index] ^= 1;
+            }
+        }
+    }
+
+    (vram, collision)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    #[test]
+    fn dxyn_matches_reference(
+        x: u8,
+        y: u8,
+        n in 0usize..16,
+        sprite in prop::collection::vec(any::<u8>(), 32),
+        screen in prop::collection::vec(0u8..2, 0x2000),
+        is_highres: bool,
+        wrap_x: bool,
+        wrap_y: bool,
+    ) {
+        // 0x200: DRW V0, V1, n
+        let mut cpu = cpu_with_rom(&[0xD0, 0x10 | n as u8]);
+        cpu.is_highres = is_highres;
+        cpu.set_wrap(wrap_x, wrap_y);
+        cpu.reg[0] = x;
+        cpu.reg[1] = y;
+        cpu.id = 0x300;
+        cpu.ram[0x300..0x320].copy_from_slice(&sprite);
+        cpu.vram.copy_from_slice(&screen);
+
+        let (wide, length) = if n == 0 { (true, 32) } else { (false, n) };
+        let (vram, collision) = reference_draw(&cpu, x, y, &sprite[..length], wide);
+
+        cpu.execute_cycle().unwrap();
+
+        prop_assert_eq!(&cpu.vram[..], &vram[..]);
+        prop_assert_eq!(cpu.reg[0xF], collision as u8);
+    }
+}
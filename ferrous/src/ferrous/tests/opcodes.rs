@@ -0,0 +1,698 @@
+//! One focused test per opcode.
+
+use super::*;
+
+/// Load the opcode at 0x200, apply the setup and execute it.
+fn execute(opcode: u16, setup: impl FnOnce(&mut Ferrous)) -> Ferrous {
+    let mut cpu = cpu_with_rom(&opcode.to_be_bytes());
+    setup(&mut cpu);
+
+    assert_eq!(cpu.execute_cycle(), Some(opcode));
+
+    cpu
+}
+
+/// Get the lit pixels of the active screen as (x, y) pairs.
+fn lit_pixels(cpu: &Ferrous) -> Vec<(usize, usize)> {
+    cpu.vram_rows()
+        .enumerate()
+        .flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, &pixel)| pixel != 0)
+                .map(move |(x, _)| (x, y))
+        })
+        .collect()
+}
+
+#[test]
+fn op_00e0_clears_display() {
+    let cpu = execute(0x00E0, |cpu| cpu.vram[..64].fill(1));
+
+    assert!(lit_pixels(&cpu).is_empty());
+    assert_eq!(cpu.pc, 0x202);
+}
+
+#[test]
+fn op_00ee_returns_from_subroutine() {
+    let cpu = execute(0x00EE, |cpu| {
+        cpu.stack[0] = 0x346;
+        cpu.sp = 1;
+    });
+
+    assert_eq!(cpu.sp, 0);
+    assert_eq!(cpu.pc, 0x346);
+}
+
+#[test]
+fn op_1nnn_jumps() {
+    let cpu = execute(0x1ABC, |_| {});
+
+    assert_eq!(cpu.pc, 0xABC);
+}
+
+#[test]
+fn op_2nnn_calls_subroutine() {
+    let cpu = execute(0x2456, |_| {});
+
+    assert_eq!(cpu.stack[0], 0x202);
+    assert_eq!(cpu.sp, 1);
+    assert_eq!(cpu.pc, 0x456);
+}
+
+#[test]
+fn op_3xkk_skips_if_equal() {
+    assert_eq!(execute(0x3342, |cpu| cpu.reg[3] = 0x42).pc, 0x204);
+    assert_eq!(execute(0x3342, |cpu| cpu.reg[3] = 0x41).pc, 0x202);
+}
+
+#[test]
+fn op_4xkk_skips_if_not_equal() {
+    assert_eq!(execute(0x4342, |cpu| cpu.reg[3] = 0x41).pc, 0x204);
+    assert_eq!(execute(0x4342, |cpu| cpu.reg[3] = 0x42).pc, 0x202);
+}
+
+#[test]
+fn op_5xy0_skips_if_registers_equal() {
+    let equal = execute(0x5120, |cpu| {
+        cpu.reg[1] = 0x10;
+        cpu.reg[2] = 0x10;
+    });
+
+    let different = execute(0x5120, |cpu| {
+        cpu.reg[1] = 0x10;
+        cpu.reg[2] = 0x11;
+    });
+
+    assert_eq!(equal.pc, 0x204);
+    assert_eq!(different.pc, 0x202);
+}
+
+#[test]
+fn op_6xkk_loads_byte() {
+    let cpu = execute(0x6A7F, |_| {});
+
+    assert_eq!(cpu.reg[0xA], 0x7F);
+    assert_eq!(cpu.pc, 0x202);
+}
+
+#[test]
+fn op_7xkk_adds_byte_without_carry() {
+    let cpu = execute(0x7102, |cpu| {
+        cpu.reg[1] = 0xFF;
+        cpu.reg[0xF] = 0x05;
+    });
+
+    assert_eq!(cpu.reg[1], 0x01);
+    assert_eq!(cpu.reg[0xF], 0x05);
+}
+
+#[test]
+fn op_8xy0_copies_register() {
+    let cpu = execute(0x8120, |cpu| cpu.reg[2] = 0x33);
+
+    assert_eq!(cpu.reg[1], 0x33);
+    assert_eq!(cpu.pc, 0x202);
+}
+
+#[test]
+fn op_8xy1_ors_registers() {
+    let cpu = execute(0x8121, |cpu| {
+        cpu.reg[1] = 0b1100;
+        cpu.reg[2] = 0b1010;
+    });
+
+    assert_eq!(cpu.reg[1], 0b1110);
+}
+
+#[test]
+fn op_8xy2_ands_registers() {
+    let cpu = execute(0x8122, |cpu| {
+        cpu.reg[1] = 0b1100;
+        cpu.reg[2] = 0b1010;
+    });
+
+    assert_eq!(cpu.reg[1], 0b1000);
+}
+
+#[test]
+fn op_8xy3_xors_registers() {
+    let cpu = execute(0x8123, |cpu| {
+        cpu.reg[1] = 0b1100;
+        cpu.reg[2] = 0b1010;
+    });
+
+    assert_eq!(cpu.reg[1], 0b0110);
+}
+
+#[test]
+fn logic_quirk_resets_vf() {
+    for opcode in [0x8121, 0x8122, 0x8123] {
+        let cpu = execute(opcode, |cpu| {
+            cpu.set_logic(true);
+            cpu.reg[0xF] = 1;
+        });
+
+        assert_eq!(cpu.reg[0xF], 0, "{:04X}", opcode);
+    }
+}
+
+#[test]
+fn logic_ops_preserve_vf_without_the_quirk() {
+    for opcode in [0x8121, 0x8122, 0x8123] {
+        let cpu = execute(opcode, |cpu| cpu.reg[0xF] = 1);
+
+        assert_eq!(cpu.reg[0xF], 1, "{:04X}", opcode);
+    }
+}
+
+#[test]
+fn op_8xy4_adds_with_carry() {
+    let carry = execute(0x8124, |cpu| {
+        cpu.reg[1] = 0xF0;
+        cpu.reg[2] = 0x20;
+    });
+
+    let no_carry = execute(0x8124, |cpu| {
+        cpu.reg[1] = 0x10;
+        cpu.reg[2] = 0x20;
+    });
+
+    assert_eq!((carry.reg[1], carry.reg[0xF]), (0x10, 1));
+    assert_eq!((no_carry.reg[1], no_carry.reg[0xF]), (0x30, 0));
+}
+
+#[test]
+fn op_8xy5_subtracts_with_not_borrow() {
+    let no_borrow = execute(0x8125, |cpu| {
+        cpu.reg[1] = 0x30;
+        cpu.reg[2] = 0x10;
+    });
+
+    let borrow = execute(0x8125, |cpu| {
+        cpu.reg[1] = 0x10;
+        cpu.reg[2] = 0x30;
+    });
+
+    assert_eq!((no_borrow.reg[1], no_borrow.reg[0xF]), (0x20, 1));
+    assert_eq!((borrow.reg[1], borrow.reg[0xF]), (0xE0, 0));
+}
+
+#[test]
+fn op_8xy6_shifts_vy_right() {
+    let cpu = execute(0x8126, |cpu| {
+        cpu.reg[1] = 0x00;
+        cpu.reg[2] = 0b0000_0101;
+    });
+
+    assert_eq!((cpu.reg[1], cpu.reg[0xF]), (0b0000_0010, 1));
+}
+
+#[test]
+fn op_8xy6_shifts_vx_right_with_quirk() {
+    let cpu = execute(0x8126, |cpu| {
+        cpu.set_shift(true);
+        cpu.reg[1] = 0b0000_0100;
+        cpu.reg[2] = 0b0000_0101;
+    });
+
+    assert_eq!((cpu.reg[1], cpu.reg[0xF]), (0b0000_0010, 0));
+}
+
+#[test]
+fn op_8xy7_subtracts_reversed_with_not_borrow() {
+    let no_borrow = execute(0x8127, |cpu| {
+        cpu.reg[1] = 0x10;
+        cpu.reg[2] = 0x30;
+    });
+
+    let borrow = execute(0x8127, |cpu| {
+        cpu.reg[1] = 0x30;
+        cpu.reg[2] = 0x10;
+    });
+
+    assert_eq!((no_borrow.reg[1], no_borrow.reg[0xF]), (0x20, 1));
+    assert_eq!((borrow.reg[1], borrow.reg[0xF]), (0xE0, 0));
+}
+
+#[test]
+fn op_8xye_shifts_vy_left() {
+    let cpu = execute(0x812E, |cpu| {
+        cpu.reg[1] = 0x00;
+        cpu.reg[2] = 0b1000_0001;
+    });
+
+    assert_eq!((cpu.reg[1], cpu.reg[0xF]), (0b0000_0010, 1));
+}
+
+#[test]
+fn op_8xye_shifts_vx_left_with_quirk() {
+    let cpu = execute(0x812E, |cpu| {
+        cpu.set_shift(true);
+        cpu.reg[1] = 0b0100_0000;
+        cpu.reg[2] = 0b1000_0001;
+    });
+
+    assert_eq!((cpu.reg[1], cpu.reg[0xF]), (0b1000_0000, 0));
+}
+
+#[test]
+fn op_9xy0_skips_if_registers_differ() {
+    let different = execute(0x9120, |cpu| {
+        cpu.reg[1] = 0x10;
+        cpu.reg[2] = 0x11;
+    });
+
+    let equal = execute(0x9120, |cpu| {
+        cpu.reg[1] = 0x10;
+        cpu.reg[2] = 0x10;
+    });
+
+    assert_eq!(different.pc, 0x204);
+    assert_eq!(equal.pc, 0x202);
+}
+
+#[test]
+fn op_annn_loads_index() {
+    let cpu = execute(0xA123, |_| {});
+
+    assert_eq!(cpu.id, 0x123);
+    assert_eq!(cpu.pc, 0x202);
+}
+
+#[test]
+fn op_bnnn_jumps_with_v0_offset() {
+    let cpu = execute(0xB300, |cpu| {
+        cpu.reg[0] = 0x10;
+        cpu.reg[3] = 0x20;
+    });
+
+    assert_eq!(cpu.pc, 0x310);
+}
+
+#[test]
+fn op_bnnn_jumps_with_vx_offset_with_quirk() {
+    let cpu = execute(0xB300, |cpu| {
+        cpu.set_jump(true);
+        cpu.reg[0] = 0x10;
+        cpu.reg[3] = 0x20;
+    });
+
+    assert_eq!(cpu.pc, 0x320);
+}
+
+#[test]
+fn op_bnnn_follows_the_quirk_profile() {
+    let profiles = [
+        (QuirkProfile::Cosmac, 0x310),
+        (QuirkProfile::XoChip, 0x310),
+        (QuirkProfile::SchipModern, 0x320),
+        (QuirkProfile::SchipLegacy, 0x320),
+    ];
+
+    for &(profile, target) in profiles.iter() {
+        let cpu = execute(0xB300, |cpu| {
+            cpu.apply_profile(profile);
+            cpu.reg[0] = 0x10;
+            cpu.reg[3] = 0x20;
+        });
+
+        assert_eq!(cpu.pc, target, "{:?}", profile);
+    }
+}
+
+#[test]
+fn op_cxkk_masks_random_byte() {
+    for _ in 0..32 {
+        assert_eq!(execute(0xC10F, |_| {}).reg[1] & 0xF0, 0);
+    }
+
+    assert_eq!(execute(0xC100, |cpu| cpu.reg[1] = 0xFF).reg[1], 0);
+}
+
+#[test]
+fn op_dxyn_draws_sprite() {
+    let cpu = execute(0xD122, |cpu| {
+        cpu.set_memory_region(0x300, &[0b1100_0000, 0b0010_0000])
+            .unwrap();
+
+        cpu.id = 0x300;
+        cpu.reg[1] = 10;
+        cpu.reg[2] = 5;
+    });
+
+    assert_eq!(lit_pixels(&cpu), [(10, 5), (11, 5), (12, 6)]);
+    assert_eq!(cpu.reg[0xF], 0);
+}
+
+#[test]
+fn op_dxyn_sets_collision_and_erases() {
+    let cpu = execute(0xD121, |cpu| {
+        cpu.set_memory_region(0x300, &[0b1000_0000]).unwrap();
+
+        cpu.id = 0x300;
+        cpu.vram[0] = 1;
+    });
+
+    assert!(lit_pixels(&cpu).is_empty());
+    assert_eq!(cpu.reg[0xF], 1);
+}
+
+#[test]
+fn op_dxyn_wraps_around_the_screen() {
+    let cpu = execute(0xD121, |cpu| {
+        cpu.set_memory_region(0x300, &[0b1100_0000]).unwrap();
+
+        cpu.id = 0x300;
+        cpu.reg[1] = 63;
+        cpu.reg[2] = 31;
+    });
+
+    assert_eq!(lit_pixels(&cpu), [(0, 31), (63, 31)]);
+}
+
+/// Draw a 4x4 block at (62, 30), straddling the bottom right corner.
+fn draw_corner_block(wrap_x: bool, wrap_y: bool) -> Ferrous {
+    execute(0xD124, |cpu| {
+        cpu.set_memory_region(0x300, &[0xF0; 4]).unwrap();
+        cpu.set_wrap(wrap_x, wrap_y);
+
+        cpu.id = 0x300;
+        cpu.reg[1] = 62;
+        cpu.reg[2] = 30;
+    })
+}
+
+#[test]
+fn op_dxyn_wraps_both_edges() {
+    let cpu = draw_corner_block(true, true);
+
+    assert_eq!(lit_pixels(&cpu).len(), 16);
+    assert!(lit_pixels(&cpu).contains(&(1, 1)));
+}
+
+#[test]
+fn op_dxyn_wraps_horizontally_only() {
+    let cpu = draw_corner_block(true, false);
+
+    assert_eq!(
+        lit_pixels(&cpu),
+        [
+            (0, 30),
+            (1, 30),
+            (62, 30),
+            (63, 30),
+            (0, 31),
+            (1, 31),
+            (62, 31),
+            (63, 31)
+        ]
+    );
+}
+
+#[test]
+fn op_dxyn_wraps_vertically_only() {
+    let cpu = draw_corner_block(false, true);
+
+    assert_eq!(
+        lit_pixels(&cpu),
+        [
+            (62, 0),
+            (63, 0),
+            (62, 1),
+            (63, 1),
+            (62, 30),
+            (63, 30),
+            (62, 31),
+            (63, 31)
+        ]
+    );
+}
+
+#[test]
+fn op_dxyn_clips_both_edges() {
+    let cpu = draw_corner_block(false, false);
+
+    assert_eq!(lit_pixels(&cpu), [(62, 30), (63, 30), (62, 31), (63, 31)]);
+}
+
+#[test]
+fn op_dxyn_wraps_position_when_clipping() {
+    let cpu = execute(0xD121, |cpu| {
+        cpu.set_memory_region(0x300, &[0x80]).unwrap();
+        cpu.set_wrap(false, false);
+
+        cpu.id = 0x300;
+        cpu.reg[1] = 64 + 3;
+        cpu.reg[2] = 32 + 2;
+    });
+
+    assert_eq!(lit_pixels(&cpu), [(3, 2)]);
+}
+
+#[test]
+fn op_dxy0_draws_16x16_sprite() {
+    let cpu = execute(0xD120, |cpu| {
+        let mut sprite = [0; 32];
+        sprite[0] = 0x80;
+        sprite[31] = 0x01;
+
+        cpu.set_memory_region(0x300, &sprite).unwrap();
+        cpu.is_highres = true;
+        cpu.id = 0x300;
+    });
+
+    assert_eq!(lit_pixels(&cpu), [(0, 0), (15, 15)]);
+}
+
+#[test]
+fn op_ex9e_skips_if_key_pressed() {
+    let pressed = execute(0xE19E, |cpu| {
+        cpu.reg[1] = 0x7;
+        cpu.set_key_at_index(0x7, true);
+    });
+
+    let released = execute(0xE19E, |cpu| cpu.reg[1] = 0x7);
+
+    assert_eq!(pressed.pc, 0x204);
+    assert_eq!(released.pc, 0x202);
+}
+
+#[test]
+fn op_exa1_skips_if_key_not_pressed() {
+    let pressed = execute(0xE1A1, |cpu| {
+        cpu.reg[1] = 0x7;
+        cpu.set_key_at_index(0x7, true);
+    });
+
+    let released = execute(0xE1A1, |cpu| cpu.reg[1] = 0x7);
+
+    assert_eq!(pressed.pc, 0x202);
+    assert_eq!(released.pc, 0x204);
+}
+
+#[test]
+fn op_fx07_reads_delay_timer() {
+    let cpu = execute(0xF407, |cpu| cpu.dt = 0x3C);
+
+    assert_eq!(cpu.reg[4], 0x3C);
+    assert_eq!(cpu.pc, 0x202);
+}
+
+#[test]
+fn op_fx0a_waits_for_key() {
+    let cpu = execute(0xF40A, |_| {});
+
+    assert_eq!(cpu.pc, 0x200);
+}
+
+#[test]
+fn op_fx0a_reads_pressed_key() {
+    let cpu = execute(0xF40A, |cpu| cpu.set_key_at_index(0xB, true));
+
+    assert_eq!(cpu.reg[4], 0xB);
+    assert_eq!(cpu.pc, 0x202);
+}
+
+#[test]
+fn op_fx15_sets_delay_timer() {
+    let cpu = execute(0xF415, |cpu| cpu.reg[4] = 0x20);
+
+    assert_eq!(cpu.dt, 0x20);
+}
+
+#[test]
+fn op_fx18_sets_sound_timer() {
+    let cpu = execute(0xF418, |cpu| cpu.reg[4] = 0x20);
+
+    assert_eq!(cpu.st, 0x20);
+}
+
+#[test]
+fn op_fx1e_adds_to_index() {
+    let cpu = execute(0xF41E, |cpu| {
+        cpu.id = 0x300;
+        cpu.reg[4] = 0x20;
+    });
+
+    assert_eq!(cpu.id, 0x320);
+}
+
+#[test]
+fn op_fx29_points_to_font_sprite() {
+    let cpu = execute(0xF429, |cpu| cpu.reg[4] = 0xA);
+
+    assert_eq!(cpu.id, 0xA * 5);
+    assert_eq!(&cpu.ram[cpu.id..cpu.id + 5], &FONT_SPRITES[50..55]);
+}
+
+#[test]
+fn op_fx29_masks_digit_to_low_nibble() {
+    let cpu = execute(0xF429, |cpu| cpu.reg[4] = 0x1A);
+
+    assert_eq!(cpu.id, 0x32);
+}
+
+#[test]
+fn op_fx33_stores_bcd() {
+    let cpu = execute(0xF433, |cpu| {
+        cpu.id = 0x300;
+        cpu.reg[4] = 254;
+    });
+
+    assert_eq!(&cpu.ram[0x300..0x303], &[2, 5, 4]);
+    assert_eq!(cpu.id, 0x300);
+}
+
+#[test]
+fn op_fx55_stores_registers() {
+    let cpu = execute(0xF255, |cpu| {
+        cpu.id = 0x300;
+        cpu.reg[..4].copy_from_slice(&[1, 2, 3, 4]);
+    });
+
+    assert_eq!(&cpu.ram[0x300..0x304], &[1, 2, 3, 0]);
+}
+
+#[test]
+fn op_fx65_loads_registers() {
+    let cpu = execute(0xF265, |cpu| {
+        cpu.set_memory_region(0x300, &[1, 2, 3, 4]).unwrap();
+        cpu.id = 0x300;
+    });
+
+    assert_eq!(&cpu.reg[..4], &[1, 2, 3, 0]);
+}
+
+#[test]
+fn op_00cn_scrolls_down() {
+    let cpu = execute(0x00C2, |cpu| {
+        cpu.vram[0] = 1;
+        cpu.vram[64 * 31] = 1;
+    });
+
+    assert_eq!(lit_pixels(&cpu), [(0, 2)]);
+}
+
+#[test]
+fn scrolling_down_by_the_screen_height_clears_it() {
+    let mut cpu = Ferrous::new();
+    cpu.vram[..64 * 32].fill(1);
+
+    cpu.apply_draw_event(&DrawEvent::ScrollDown(32));
+
+    assert!(lit_pixels(&cpu).is_empty());
+}
+
+#[test]
+fn scrolling_down_past_the_screen_height_clears_it() {
+    let mut cpu = Ferrous::new();
+    cpu.switch_resolution(true);
+    cpu.vram.fill(1);
+
+    cpu.apply_draw_event(&DrawEvent::ScrollDown(u8::MAX));
+
+    assert!(lit_pixels(&cpu).is_empty());
+}
+
+#[test]
+fn op_00fb_scrolls_right() {
+    let cpu = execute(0x00FB, |cpu| {
+        cpu.vram[0] = 1;
+        cpu.vram[63] = 1;
+    });
+
+    assert_eq!(lit_pixels(&cpu), [(4, 0)]);
+}
+
+#[test]
+fn op_00fc_scrolls_left() {
+    let cpu = execute(0x00FC, |cpu| {
+        cpu.vram[0] = 1;
+        cpu.vram[4] = 1;
+    });
+
+    assert_eq!(lit_pixels(&cpu), [(0, 0)]);
+}
+
+#[test]
+fn op_00fd_halts() {
+    let mut cpu = execute(0x00FD, |_| {});
+
+    assert!(cpu.is_halted);
+    assert_eq!(cpu.execute_cycle(), None);
+}
+
+#[test]
+fn op_00fe_disables_high_resolution() {
+    let cpu = execute(0x00FE, |cpu| {
+        cpu.is_highres = true;
+        cpu.vram[0] = 1;
+    });
+
+    assert!(!cpu.is_highres);
+    assert!(lit_pixels(&cpu).is_empty());
+}
+
+#[test]
+fn op_00ff_enables_high_resolution() {
+    let cpu = execute(0x00FF, |cpu| cpu.vram[0] = 1);
+
+    assert!(cpu.is_highres);
+    assert_eq!(cpu.get_height_width(), (64, 128));
+    assert!(lit_pixels(&cpu).is_empty());
+}
+
+#[test]
+fn op_fx30_points_to_high_resolution_font_sprite() {
+    let cpu = execute(0xF430, |cpu| cpu.reg[4] = 0x3);
+
+    assert_eq!(cpu.id, 80 + 0x3 * 10);
+    assert_eq!(
+        &cpu.ram[cpu.id..cpu.id + 10],
+        &HIGH_RES_FONT_SPRITES[30..40]
+    );
+}
+
+#[test]
+fn op_fx30_masks_digit_to_low_nibble() {
+    let cpu = execute(0xF430, |cpu| cpu.reg[4] = 0xFF);
+
+    assert_eq!(cpu.id, 80 + 0xF * 10);
+}
+
+#[test]
+fn op_fx75_stores_flag_registers() {
+    let cpu = execute(0xF275, |cpu| cpu.reg[..4].copy_from_slice(&[1, 2, 3, 4]));
+
+    assert_eq!(cpu.flag_registers(), &[1, 2, 3, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn op_fx85_loads_flag_registers() {
+    let cpu = execute(0xF285, |cpu| {
+        cpu.set_flag_registers(&[1, 2, 3, 4, 5, 6, 7, 8])
+    });
+
+    assert_eq!(&cpu.reg[..4], &[1, 2, 3, 0]);
+}
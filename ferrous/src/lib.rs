@@ -1,9 +1,59 @@
 //! A full featured pure Rust implementation
 //! of a (super) Chip8 interpreter.
+//!
+//! # Example
+//!
+//! ```rust
+//! use ferrous::prelude::*;
+//!
+//! let mut cpu = CPU::new();
+//! cpu.apply_profile(QuirkProfile::SchipModern);
+//!
+//! // 0x200: LD V0, 0x2A
+//! cpu.load_rom(&[0x60, 0x2A]).unwrap();
+//!
+//! let opcode = cpu.execute_cycle().unwrap();
+//!
+//! assert_eq!(Instruction::decode(opcode), Some(Instruction::LdByte(0, 0x2A)));
+//! assert_eq!(cpu.reg[0], 0x2A);
+//! ```
 
 #![no_std]
 
+extern crate alloc;
+
+mod clock;
+mod delta;
+mod disasm;
+mod features;
 mod ferrous;
 mod font;
+mod lockstep;
+mod quirks;
+mod resolution;
+mod snapshot;
+mod trace;
 
+pub use self::clock::*;
+pub use self::delta::*;
+pub use self::disasm::*;
+pub use self::features::*;
 pub use self::ferrous::*;
+pub use self::lockstep::*;
+pub use self::quirks::*;
+pub use self::resolution::*;
+pub use self::snapshot::*;
+pub use self::trace::*;
+
+/// The interpreter, under the name used throughout the documentation.
+pub type CPU = Ferrous;
+
+/// Re-exports the types needed to embed the interpreter.
+pub mod prelude {
+    pub use crate::{
+        disassemble, trace_disassemble, Category, CpuEvent, CycleDelta, DisplaySnapshot, DrawEvent,
+        FeatureSet, Ferrous, InputRecording, Instruction, InvalidOpcodePolicy, Listing,
+        ListingItem, LoadStoreMode, MemoryRegion, QuirkProfile, Resolution, StackError,
+        StepSummary, StopReason, TickClock, CPU,
+    };
+}
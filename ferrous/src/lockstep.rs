@@ -0,0 +1,63 @@
+//! Contains the input recordings replayed by lockstep mode,
+//! see `Ferrous::enter_lockstep`.
+
+#[cfg(feature = "savestates")]
+use serde::{Deserialize, Serialize};
+
+use alloc::vec::Vec;
+
+/// Keys held on each frame of a run, with the cycles executed per frame.
+///
+/// Only the frames where the held keys change are stored.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "savestates", derive(Serialize, Deserialize))]
+pub struct InputRecording {
+    /// CPU cycles executed on every frame.
+    pub cycles_per_frame: u16,
+
+    /// Frames where the held keys changed, and the keys held
+    /// from then on, one bit per key.
+    changes: Vec<(u32, u16)>,
+}
+
+impl InputRecording {
+    /// Create an empty recording, where no keys are ever held.
+    pub fn new(cycles_per_frame: u16) -> Self {
+        Self {
+            cycles_per_frame,
+            changes: Vec::new(),
+        }
+    }
+
+    /// Record the keys held on the given frame, one bit per key.
+    ///
+    /// Frames must be recorded in ascending order, recording
+    /// an earlier frame than the last one is ignored.
+    pub fn record(&mut self, frame: u32, keys: u16) {
+        match self.changes.last_mut() {
+            Some((last, _)) if frame < *last => {}
+            Some((last, held)) if frame == *last => *held = keys,
+            Some(&mut (_, held)) if held == keys => {}
+            None if keys == 0 => {}
+            _ => self.changes.push((frame, keys)),
+        }
+    }
+
+    /// Get the keys held on the given frame, one bit per key.
+    pub fn keys_at(&self, frame: u32) -> u16 {
+        let index = self.changes.partition_point(|&(start, _)| start <= frame);
+
+        match index {
+            0 => 0,
+            _ => self.changes[index - 1].1,
+        }
+    }
+}
+
+/// A recording being replayed, and the next frame to replay.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "savestates", derive(Serialize, Deserialize))]
+pub(crate) struct Lockstep {
+    pub recording: InputRecording,
+    pub frame: u32,
+}
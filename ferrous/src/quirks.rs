@@ -0,0 +1,78 @@
+//! Contains presets for the ambiguous instruction behaviours
+//! of the various Chip-8 platforms.
+
+#[cfg(feature = "savestates")]
+use serde::{Deserialize, Serialize};
+
+use core::str::FromStr;
+
+/// How the index register is advanced after Fx55 and Fx65.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "savestates", derive(Serialize, Deserialize))]
+pub enum LoadStoreMode {
+    /// Increment I by X + 1, like the COSMAC VIP.
+    #[default]
+    IncrementByXPlus1,
+
+    /// Increment I by X, like Super Chip 1.0.
+    IncrementByX,
+
+    /// Leave I unchanged, like Super Chip 1.1.
+    Unchanged,
+}
+
+/// Quirk presets matching the behaviour of well known platforms.
+///
+/// Each platform also has a typical speed, see `instructions_per_second`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuirkProfile {
+    /// The original COSMAC VIP Chip-8 interpreter.
+    Cosmac,
+
+    /// Super Chip as implemented by most modern interpreters.
+    SchipModern,
+
+    /// The original Super Chip 1.x interpreter on the HP48.
+    SchipLegacy,
+
+    /// Octo's XO-Chip extension.
+    XoChip,
+}
+
+impl QuirkProfile {
+    /// Get the instructions per second ROMs for the platform are tuned for.
+    ///
+    /// | Profile        | IPS   | Per 60 Hz tick |
+    /// |----------------|-------|----------------|
+    /// | `Cosmac`       | 700   | ~11.7          |
+    /// | `SchipLegacy`  | 1000  | ~16.7          |
+    /// | `SchipModern`  | 1800  | 30             |
+    /// | `XoChip`       | 6000  | 100            |
+    ///
+    /// The COSMAC VIP's rate varies by instruction, 700 is the usual approximation.
+    /// The HP48 ran Super Chip somewhat faster, and modern Super Chip and
+    /// XO-Chip ROMs, written for Octo, commonly assume faster still.
+    pub fn instructions_per_second(self) -> u32 {
+        match self {
+            Self::Cosmac => 700,
+            Self::SchipLegacy => 1000,
+            Self::SchipModern => 1800,
+            Self::XoChip => 6000,
+        }
+    }
+}
+
+impl FromStr for QuirkProfile {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cosmac" => Ok(Self::Cosmac),
+            "schip-modern" => Ok(Self::SchipModern),
+            "schip-legacy" => Ok(Self::SchipLegacy),
+            "xochip" => Ok(Self::XoChip),
+
+            _ => Err("Unknown quirk profile."),
+        }
+    }
+}
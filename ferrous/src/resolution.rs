@@ -0,0 +1,48 @@
+//! Contains the screen dimensions of the display modes.
+
+use core::ops::Range;
+
+/// Dimensions of the screen in one of the display modes.
+///
+/// VRAM is sized for the largest resolution, and smaller ones use
+/// its start, one row after another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Resolution {
+    /// Number of pixel columns.
+    pub width: usize,
+
+    /// Number of pixel rows.
+    pub height: usize,
+}
+
+impl Resolution {
+    /// The standard Chip-8 resolution.
+    pub const LOW: Self = Self {
+        width: 64,
+        height: 32,
+    };
+
+    /// The Super Chip extended resolution.
+    pub const HIGH: Self = Self {
+        width: 128,
+        height: 64,
+    };
+
+    /// The largest resolution, which VRAM is sized for.
+    pub const MAX: Self = Self::HIGH;
+
+    /// Get the number of pixels on the screen.
+    pub const fn pixels(self) -> usize {
+        self.width * self.height
+    }
+
+    /// Get the index in VRAM of the pixel at (x, y).
+    pub const fn index(self, x: usize, y: usize) -> usize {
+        y * self.width + x
+    }
+
+    /// Get the range of VRAM holding the given row.
+    pub fn row(self, y: usize) -> Range<usize> {
+        self.index(0, y)..self.index(0, y + 1)
+    }
+}
@@ -0,0 +1,29 @@
+//! Contains snapshots of the display, for handing
+//! frames to a renderer without copying the whole CPU.
+
+use alloc::vec::Vec;
+
+use crate::resolution::Resolution;
+
+/// A copy of the active screen, see `Ferrous::clone_display_only`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisplaySnapshot {
+    /// Resolution of the screen when the snapshot was taken.
+    pub resolution: Resolution,
+
+    /// Pixels of the screen, one byte per pixel, row after row.
+    pub pixels: Vec<u8>,
+}
+
+impl DisplaySnapshot {
+    /// Is this a snapshot of the Super Chip extended resolution?
+    pub fn is_highres(&self) -> bool {
+        self.resolution == Resolution::HIGH
+    }
+
+    /// Iterate over the rows of the screen, each
+    /// row being a slice as wide as the resolution.
+    pub fn rows(&self) -> impl Iterator<Item = &[u8]> {
+        self.pixels.chunks_exact(self.resolution.width)
+    }
+}
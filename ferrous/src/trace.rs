@@ -0,0 +1,104 @@
+//! Contains execution traces, for checking the interpreter
+//! against a reference implementation.
+//!
+//! A trace file lists one executed instruction per line, as the hex
+//! program counter followed by the hex opcode, e.g. `0200 6005`.
+//! Empty lines and lines starting with `#` are ignored.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+/// One executed instruction of a trace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceEntry {
+    /// Address the opcode was fetched from.
+    pub pc: usize,
+
+    /// The opcode that was executed.
+    pub opcode: u16,
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04X} {:04X}", self.pc, self.opcode)
+    }
+}
+
+/// Why a trace couldn't be parsed, see `parse_trace`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceError {
+    /// Line of the error, starting from 1.
+    pub line: usize,
+
+    /// What was wrong with the line.
+    pub reason: &'static str,
+}
+
+impl fmt::Display for TraceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+/// Where execution first diverged from a trace, see `Ferrous::compare_trace`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Divergence {
+    /// Index of the diverging entry in the trace.
+    pub cycle: usize,
+
+    /// The entry of the trace.
+    pub expected: TraceEntry,
+
+    /// What the interpreter was about to execute instead,
+    /// `None` if it had halted or run off the end of memory.
+    pub actual: Option<TraceEntry>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cycle {}: expected {}, ", self.cycle, self.expected)?;
+
+        match self.actual {
+            Some(actual) => write!(f, "found {}", actual),
+            None => write!(f, "but the interpreter stopped"),
+        }
+    }
+}
+
+/// Parse the given trace.
+pub fn parse_trace(text: &str) -> Result<Vec<TraceEntry>, TraceError> {
+    let mut entries = Vec::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let error = |reason| TraceError {
+            line: number + 1,
+            reason,
+        };
+
+        let mut fields = line.split_whitespace();
+
+        let pc = fields
+            .next()
+            .and_then(|pc| usize::from_str_radix(pc, 16).ok())
+            .ok_or_else(|| error("expected a hex program counter."))?;
+
+        let opcode = fields
+            .next()
+            .and_then(|opcode| u16::from_str_radix(opcode, 16).ok())
+            .ok_or_else(|| error("expected a hex opcode."))?;
+
+        if fields.next().is_some() {
+            return Err(error("expected only a program counter and an opcode."));
+        }
+
+        entries.push(TraceEntry { pc, opcode });
+    }
+
+    Ok(entries)
+}
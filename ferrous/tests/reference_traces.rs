@@ -0,0 +1,112 @@
+//! Runs every ROM in `tests/traces` against the trace next to it,
+//! e.g. `count.ch8` against `count.trace`.
+//!
+//! Traces are expected to be made with 10 cycles per frame,
+//! and without any quirks enabled.
+
+use std::fs;
+use std::path::Path;
+
+use ferrous::{parse_trace, Divergence, Ferrous, TraceEntry};
+
+/// Cycles per frame the traces were made with.
+const CYCLES_PER_FRAME: u16 = 10;
+
+/// Lines of the trace printed before a divergence.
+const CONTEXT: usize = 4;
+
+/// Run the ROM against the trace, panicking with context on the first divergence.
+fn check(rom: &[u8], trace: &[TraceEntry], name: &str) {
+    let mut cpu = Ferrous::new();
+    cpu.load_rom(rom).unwrap();
+
+    if let Err(divergence) = cpu.compare_trace(trace, CYCLES_PER_FRAME) {
+        let Divergence { cycle, .. } = divergence;
+        let context: Vec<String> = trace[cycle.saturating_sub(CONTEXT)..cycle]
+            .iter()
+            .map(|entry| format!("    {}", entry))
+            .collect();
+
+        panic!(
+            "{} diverged at {}, after:\n{}",
+            name,
+            divergence,
+            context.join("\n")
+        );
+    }
+}
+
+#[test]
+fn roms_match_their_reference_traces() {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/traces");
+    let mut checked = 0;
+
+    for entry in fs::read_dir(directory).unwrap() {
+        let path = entry.unwrap().path();
+
+        if path.extension() != Some("trace".as_ref()) {
+            continue;
+        }
+
+        let name = path.file_stem().unwrap().to_string_lossy();
+        let rom = fs::read(path.with_extension("ch8")).unwrap();
+        let trace = parse_trace(&fs::read_to_string(&path).unwrap())
+            .unwrap_or_else(|error| panic!("{}.trace: {}", name, error));
+
+        check(&rom, &trace, &name);
+        checked += 1;
+    }
+
+    assert!(checked > 0, "no traces were found.");
+}
+
+#[test]
+fn first_divergence_is_reported() {
+    // 0x200: LD V0, 0x01, 0x202: JP 0x206
+    let rom = [0x60, 0x01, 0x12, 0x06];
+    let trace = parse_trace("0200 6001\n0202 1206\n0204 0000\n").unwrap();
+
+    let mut cpu = Ferrous::new();
+    cpu.load_rom(&rom).unwrap();
+
+    let divergence = cpu.compare_trace(&trace, CYCLES_PER_FRAME).unwrap_err();
+
+    assert_eq!(divergence.cycle, 2);
+    assert_eq!(
+        divergence.expected,
+        TraceEntry {
+            pc: 0x204,
+            opcode: 0
+        }
+    );
+    assert_eq!(
+        divergence.actual,
+        Some(TraceEntry {
+            pc: 0x206,
+            opcode: 0
+        })
+    );
+}
+
+#[test]
+fn halting_early_is_a_divergence() {
+    // 0x200: EXIT
+    let trace = parse_trace("0200 00FD\n0202 0000\n").unwrap();
+
+    let mut cpu = Ferrous::new();
+    cpu.load_rom(&[0x00, 0xFD]).unwrap();
+
+    let divergence = cpu.compare_trace(&trace, CYCLES_PER_FRAME).unwrap_err();
+
+    assert_eq!(divergence.cycle, 1);
+    assert_eq!(divergence.actual, None);
+}
+
+#[test]
+fn malformed_lines_are_rejected() {
+    let error = parse_trace("# comment\n\n0200 6001\n0202\n").unwrap_err();
+
+    assert_eq!(error.line, 4);
+    assert!(parse_trace("0200 6001 7001").is_err());
+    assert!(parse_trace("020G 6001").is_err());
+}